@@ -12,12 +12,22 @@ module.exports = async function (provider: anchor.AnchorProvider) {
     program.programId
   );
 
+  const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
   const tx = await program.methods.initialize({
     cancelTimeout: new anchor.BN(60 * 60 * 72), // 72 hours
     commissionBasisPoints: 100, // 1%
     owner: new anchor.web3.PublicKey("DFbHUAt744X6K37nygXyXjqEw9K6qAKAHDZNzeY3BP7V"), // program.provider.wallet.publicKey,
+    tenantId: new anchor.BN(0),
+    allowedMints: [],
   }).accountsStrict({
     settings: settings,
+    program: program.programId,
+    programData,
+    authority: program.provider.wallet.publicKey,
     payer: program.provider.wallet.publicKey,
     systemProgram: anchor.web3.SystemProgram.programId
   }).rpc({
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Dk9cvKLSjyp2J58ZrrLkUAN2Cj7xN14eAH9aQ5AH7qHd");
+
+/// Stand-in for an oracle in tests. The 8 byte discriminator followed by `PriceFeed`
+/// matches the layout `token-time-lock-agreement` reads.
+#[program]
+pub mod mock_price_feed {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>, params: PriceParams) -> Result<()> {
+        ctx.accounts.price_feed.authority = *ctx.accounts.payer.key;
+        ctx.accounts.price_feed.set(params);
+        Ok(())
+    }
+
+    pub fn set_price(ctx: Context<SetPrice>, params: PriceParams) -> Result<()> {
+        ctx.accounts.price_feed.set(params);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + PriceFeed::INIT_SPACE)]
+    pub price_feed: Account<'info, PriceFeed>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    #[account(mut, has_one = authority)]
+    pub price_feed: Account<'info, PriceFeed>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct PriceParams {
+    pub price: u64,
+    pub confidence: u64,
+    pub publish_time: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PriceFeed {
+    pub price: u64,
+    pub confidence: u64,
+    pub publish_time: i64,
+    pub authority: Pubkey,
+}
+
+impl PriceFeed {
+    fn set(&mut self, params: PriceParams) {
+        self.price = params.price;
+        self.confidence = params.confidence;
+        self.publish_time = params.publish_time;
+    }
+}
@@ -3,6 +3,9 @@ use anchor_lang::prelude::*;
 #[constant]
 pub const BASIC_POINTS: u64 = 10_000;
 
+#[constant]
+pub const PRICE_SCALE: u64 = 1_000_000_000;
+
 #[constant]
 pub const AGREEMENT_SEED: &str = "agreement";
 
@@ -17,3 +20,53 @@ pub const PROJECT_SEED: &str = "project";
 
 #[constant]
 pub const PAYMENT_SEED: &str = "payment";
+
+#[constant]
+pub const ROLE_SEED: &str = "role";
+
+#[constant]
+pub const PROPOSAL_SEED: &str = "proposal";
+
+#[constant]
+pub const ACKNOWLEDGEMENT_SEED: &str = "acknowledgement";
+
+#[constant]
+pub const ATTESTATION_SEED: &str = "attestation";
+
+#[constant]
+pub const BLOCKLIST_SEED: &str = "blocklist";
+
+#[constant]
+pub const WAITLIST_SEED: &str = "waitlist";
+
+#[constant]
+pub const PAYMENT_MINT_SEED: &str = "payment_mint";
+
+#[constant]
+pub const UNWRAP_SEED: &str = "unwrap";
+
+#[constant]
+pub const PAYMENT_RECEIPT_SEED: &str = "payment_receipt";
+
+#[constant]
+pub const BOND_SEED: &str = "bond";
+
+#[constant]
+pub const MILESTONES_SEED: &str = "milestones";
+
+#[constant]
+pub const REFERRER_SEED: &str = "referrer";
+
+pub const MAX_ALLOWED_MINTS: usize = 8;
+
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
+
+pub const MAX_DOCUMENT_URI_LEN: usize = 128;
+
+pub const MAX_MEMO_LEN: usize = 64;
+
+pub const MAX_MILESTONES: usize = 8;
+
+pub const MAX_REVENUE_SHARES: usize = 5;
+
+pub const MAX_FEE_SHARES: usize = 4;
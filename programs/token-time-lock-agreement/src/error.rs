@@ -70,4 +70,146 @@ pub enum ErrorCode {
     DuplicateInvestor,
     #[msg("Invalid investor account owner")]
     InvalidInvestorAccountOwner,
+    #[msg("Invalid account layout")]
+    InvalidAccountLayout,
+    #[msg("Account already migrated")]
+    AlreadyMigrated,
+    #[msg("Agreement id overflow")]
+    AgreementIdOverflow,
+    #[msg("Invalid commission")]
+    InvalidCommission,
+    #[msg("Too many allowed mints")]
+    TooManyAllowedMints,
+    #[msg("Payment mint not allowed")]
+    PaymentMintNotAllowed,
+    #[msg("Invalid multisig configuration")]
+    InvalidMultisigConfig,
+    #[msg("Not a multisig signer")]
+    NotMultisigSigner,
+    #[msg("Approved proposal required")]
+    ProposalRequired,
+    #[msg("Proposal already approved")]
+    ProposalAlreadyApproved,
+    #[msg("Proposal already executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Proposal expired")]
+    ProposalExpired,
+    #[msg("Proposal action mismatch")]
+    ProposalActionMismatch,
+    #[msg("Proposal threshold not met")]
+    ProposalThresholdNotMet,
+    #[msg("Agreement not accepted by all parties")]
+    AgreementNotAccepted,
+    #[msg("Agreement already accepted")]
+    AgreementAlreadyAccepted,
+    #[msg("Accepted terms do not match the agreement")]
+    TermsMismatch,
+    #[msg("Document URI too long")]
+    DocumentUriTooLong,
+    #[msg("Document hash mismatch")]
+    DocumentHashMismatch,
+    #[msg("Document not acknowledged by investor")]
+    DocumentNotAcknowledged,
+    #[msg("KYC attestation required")]
+    AttestationRequired,
+    #[msg("KYC attestation expired")]
+    AttestationExpired,
+    #[msg("Invalid KYC attestation")]
+    InvalidAttestation,
+    #[msg("Wallet is blocked")]
+    WalletBlocked,
+    #[msg("Invalid blocklist account")]
+    InvalidBlocklistAccount,
+    #[msg("Funding already completed")]
+    FundingCompleted,
+    #[msg("Invalid deposit window")]
+    InvalidDepositWindow,
+    #[msg("Deposit deadline passed")]
+    DepositDeadlinePassed,
+    #[msg("Deposit deadline not reached")]
+    DepositDeadlineNotReached,
+    #[msg("Invalid waitlist entry")]
+    InvalidWaitlistEntry,
+    #[msg("Invalid soft cap")]
+    InvalidSoftCap,
+    #[msg("Soft cap not reached")]
+    SoftCapNotReached,
+    #[msg("Funding deadline passed")]
+    FundingDeadlinePassed,
+    #[msg("Funding deadline not reached")]
+    FundingDeadlineNotReached,
+    #[msg("Invalid public sale terms")]
+    InvalidPublicSaleTerms,
+    #[msg("Public sale not enabled")]
+    PublicSaleNotEnabled,
+    #[msg("Ticket below minimum")]
+    TicketBelowMinimum,
+    #[msg("Ticket above maximum")]
+    TicketAboveMaximum,
+    #[msg("Wallet cap exceeded")]
+    WalletCapExceeded,
+    #[msg("Invalid commit window")]
+    InvalidCommitWindow,
+    #[msg("Commit window not enabled")]
+    CommitWindowNotEnabled,
+    #[msg("Commit window closed")]
+    CommitWindowClosed,
+    #[msg("Commit window still open")]
+    CommitWindowOpen,
+    #[msg("Commitments below target")]
+    CommitmentsBelowTarget,
+    #[msg("Allocations are computed at settlement")]
+    ProRataAllocation,
+    #[msg("Payment receipt required")]
+    PaymentReceiptRequired,
+    #[msg("Payment receipt only records direct payments")]
+    InvalidPaymentReceipt,
+    #[msg("Memo too long")]
+    MemoTooLong,
+    #[msg("Invalid payment mint")]
+    InvalidPaymentMint,
+    #[msg("Invalid conversion rate")]
+    InvalidConversionRate,
+    #[msg("Invalid price feed")]
+    InvalidPriceFeed,
+    #[msg("Stale price")]
+    StalePrice,
+    #[msg("Price confidence too wide")]
+    PriceConfidenceTooWide,
+    #[msg("Invalid oracle terms")]
+    InvalidOracleTerms,
+    #[msg("Payment exceeds the maximum amount given")]
+    SlippageExceeded,
+    #[msg("Invalid seller bond")]
+    InvalidSellerBond,
+    #[msg("Seller bond accounts required")]
+    SellerBondAccountsRequired,
+    #[msg("Seller bond cannot be slashed")]
+    SellerBondNotSlashable,
+    #[msg("Seller bond was not slashed to investors")]
+    BondNotSlashedToInvestors,
+    #[msg("Bond share already claimed")]
+    BondShareAlreadyClaimed,
+    #[msg("Invalid milestones")]
+    InvalidMilestones,
+    #[msg("Milestone already approved")]
+    MilestoneAlreadyApproved,
+    #[msg("Invalid revenue split")]
+    InvalidRevenueSplit,
+    #[msg("Invalid fee split")]
+    InvalidFeeSplit,
+    #[msg("Invalid referral")]
+    InvalidReferral,
+    #[msg("Invalid referrer account")]
+    InvalidReferrerAccount,
+    #[msg("Explicit agreement id must be below the counter")]
+    AgreementIdNotReserved,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Bond share claim window closed")]
+    BondClaimWindowClosed,
+    #[msg("Bond share claim window still open")]
+    BondClaimWindowOpen,
+    #[msg("Seller bond cannot be returned")]
+    SellerBondNotReturnable,
 }
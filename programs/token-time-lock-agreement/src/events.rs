@@ -0,0 +1,247 @@
+use anchor_lang::prelude::*;
+
+use crate::{AdminAction, RoleKind};
+
+#[event]
+pub struct AgreementInitialized {
+    pub agreement: Pubkey,
+    pub agreement_id: u64,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub settings: Pubkey,
+    pub wallet: Pubkey,
+    pub kind: RoleKind,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub settings: Pubkey,
+    pub wallet: Pubkey,
+    pub kind: RoleKind,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub action: AdminAction,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub approver: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct ProjectTokensDeposited {
+    pub agreement: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AgreementAccepted {
+    pub agreement: Pubkey,
+    pub party: Pubkey,
+}
+
+#[event]
+pub struct DocumentAcknowledged {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub document_hash: [u8; 32],
+}
+
+#[event]
+pub struct AttestationIssued {
+    pub attestation: Pubkey,
+    pub wallet: Pubkey,
+    pub jurisdiction: u16,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct AttestationRevoked {
+    pub attestation: Pubkey,
+    pub wallet: Pubkey,
+}
+
+#[event]
+pub struct WalletBlocked {
+    pub settings: Pubkey,
+    pub wallet: Pubkey,
+}
+
+#[event]
+pub struct WalletUnblocked {
+    pub settings: Pubkey,
+    pub wallet: Pubkey,
+}
+
+#[event]
+pub struct PayoutEscrowed {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EscrowReleased {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CommitmentWithdrawn {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WaitlistJoined {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub position: u64,
+}
+
+#[event]
+pub struct WaitlistEntrySkipped {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub position: u64,
+}
+
+#[event]
+pub struct AllocationExpired {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub next_wallet: Option<Pubkey>,
+}
+
+#[event]
+pub struct FundingFinalized {
+    pub agreement: Pubkey,
+    pub raised: u64,
+    pub tokens_sold: u64,
+}
+
+#[event]
+pub struct TokensPurchased {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub tokens: u64,
+}
+
+#[event]
+pub struct FundsCommitted {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CommitmentSettled {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub tokens: u64,
+    pub refund: u64,
+}
+
+#[event]
+pub struct DirectPaymentReceived {
+    pub agreement: Pubkey,
+    pub receipt: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PaymentMintAdded {
+    pub agreement: Pubkey,
+    pub mint: Pubkey,
+    pub rate: u64,
+    pub oracle: Pubkey,
+}
+
+#[event]
+pub struct PaymentConverted {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub rate: u64,
+    pub value: u64,
+}
+
+#[event]
+pub struct SellerBondReturned {
+    pub agreement: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SellerBondSlashed {
+    pub agreement: Pubkey,
+    pub amount: u64,
+    pub to_investors: bool,
+}
+
+#[event]
+pub struct BondShareClaimed {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BondSharesSwept {
+    pub agreement: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MilestoneApproved {
+    pub agreement: Pubkey,
+    pub index: u8,
+    pub approved_bps: u16,
+}
+
+#[event]
+pub struct AgreementTerminated {
+    pub agreement: Pubkey,
+    pub approved_bps: u16,
+}
+
+#[event]
+pub struct RevenueSharePaid {
+    pub agreement: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CommissionPaid {
+    pub agreement: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReferralFeePaid {
+    pub agreement: Pubkey,
+    pub referrer: Pubkey,
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
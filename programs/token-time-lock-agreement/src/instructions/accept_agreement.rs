@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
+
+use crate::{error::ErrorCode, Agreement, AgreementAccepted, AGREEMENT_SEED, BOND_SEED};
+
+#[derive(Accounts)]
+#[instruction(params: AcceptAgreementParams)]
+pub struct AcceptAgreement<'info> {
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(address = agreement.payment_token_mint @ ErrorCode::InvalidPaymentMint)]
+    pub payment_token_mint: Option<Account<'info, Mint>>,
+    /// Holds the seller bond, created when the seller accepts an agreement with one.
+    #[account(
+        init,
+        payer = payer,
+        token::mint = payment_token_mint,
+        token::authority = agreement,
+        seeds = [BOND_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bond_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub seller_payment_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Terms as the signer saw them, the call fails unless they match the agreement exactly.
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AcceptAgreementParams {
+    pub expected_payment: u64,
+    pub expected_tokens: u64,
+    pub hold_duration: i64,
+    pub payment_token_mint: Pubkey,
+    pub project_token_mint: Pubkey,
+    pub document_hash: [u8; 32],
+}
+
+impl<'info> AcceptAgreement<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: AcceptAgreementParams,
+    ) -> Result<()> {
+        let agreement = &mut ctx.accounts.agreement;
+        if agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if params.expected_payment != agreement.expected_payment
+            || params.expected_tokens != agreement.expected_tokens
+            || params.hold_duration != agreement.hold_duration
+            || params.payment_token_mint != agreement.payment_token_mint
+            || params.project_token_mint != agreement.project_token_mint
+            || params.document_hash != agreement.document_hash
+        {
+            return Err(ErrorCode::TermsMismatch.into());
+        }
+
+        let is_seller = agreement.token_seller == *ctx.accounts.payer.key;
+        let is_company = agreement.company_acceptance_required
+            && agreement.company_wallet == *ctx.accounts.payer.key;
+        if !is_seller && !is_company {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+        if (!is_seller || agreement.seller_accepted) && (!is_company || agreement.company_accepted)
+        {
+            return Err(ErrorCode::AgreementAlreadyAccepted.into());
+        }
+
+        let posts_bond = is_seller && agreement.seller_bond > 0;
+        if ctx.accounts.bond_token_account.is_some() != posts_bond {
+            return Err(ErrorCode::InvalidSellerBond.into());
+        }
+        if posts_bond {
+            let (Some(bond_token_account), Some(seller_payment_token_account)) = (
+                &ctx.accounts.bond_token_account,
+                &ctx.accounts.seller_payment_token_account,
+            ) else {
+                return Err(ErrorCode::SellerBondAccountsRequired.into());
+            };
+            anchor_spl::token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: seller_payment_token_account.to_account_info(),
+                        to: bond_token_account.to_account_info(),
+                        authority: ctx.accounts.payer.to_account_info(),
+                    },
+                ),
+                agreement.seller_bond,
+            )?;
+            agreement.bond_deposited = true;
+        }
+
+        if is_seller {
+            agreement.seller_accepted = true;
+        }
+        if is_company {
+            agreement.company_accepted = true;
+        }
+
+        emit!(AgreementAccepted {
+            agreement: agreement.key(),
+            party: *ctx.accounts.payer.key,
+        });
+
+        Ok(())
+    }
+}
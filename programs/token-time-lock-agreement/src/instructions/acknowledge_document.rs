@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, Acknowledgement, Agreement, DocumentAcknowledged, Investor,
+    ACKNOWLEDGEMENT_SEED, AGREEMENT_SEED, INVESTOR_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: AcknowledgeDocumentParams)]
+pub struct AcknowledgeDocument<'info> {
+    #[account(
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(
+        seeds = [INVESTOR_SEED.as_bytes(), agreement.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub investor: Option<Account<'info, Investor>>,
+    #[account(
+        init,
+        payer = payer,
+        space = Acknowledgement::SPACE,
+        seeds = [ACKNOWLEDGEMENT_SEED.as_bytes(), agreement.key().as_ref(), payer.key().as_ref()],
+        bump
+    )]
+    pub acknowledgement: Account<'info, Acknowledgement>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AcknowledgeDocumentParams {
+    pub document_hash: [u8; 32],
+}
+
+impl<'info> AcknowledgeDocument<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: AcknowledgeDocumentParams,
+    ) -> Result<()> {
+        if params.document_hash != ctx.accounts.agreement.document_hash {
+            return Err(ErrorCode::DocumentHashMismatch.into());
+        }
+        // Public sale buyers acknowledge before their first purchase creates the investor
+        if ctx.accounts.investor.is_none() && !ctx.accounts.agreement.public_sale() {
+            return Err(ErrorCode::InvalidInvestorAccount.into());
+        }
+
+        let acknowledgement = &mut ctx.accounts.acknowledgement;
+        acknowledgement.agreement = ctx.accounts.agreement.key();
+        acknowledgement.wallet = *ctx.accounts.payer.key;
+        acknowledgement.document_hash = params.document_hash;
+        acknowledgement.acknowledged_at = Clock::get()?.unix_timestamp;
+        acknowledgement.bump = ctx.bumps.acknowledgement;
+        acknowledgement.version = Acknowledgement::VERSION;
+
+        emit!(DocumentAcknowledged {
+            agreement: acknowledgement.agreement,
+            wallet: acknowledgement.wallet,
+            document_hash: acknowledgement.document_hash,
+        });
+
+        Ok(())
+    }
+}
@@ -1,28 +1,32 @@
-use std::mem::size_of;
-
 use anchor_lang::{
     prelude::*,
     system_program::{self, create_account, CreateAccount},
 };
 
 use crate::{
-    error::ErrorCode, Agreement, Investor, Settings, AGREEMENT_SEED, INVESTOR_SEED, SETTINGS_SEED,
+    error::ErrorCode, utils::create_pda_account, Agreement, Investor, Referrer, Role, RoleKind,
+    Settings, AGREEMENT_SEED, BASIC_POINTS, INVESTOR_SEED, REFERRER_SEED, ROLE_SEED, SETTINGS_SEED,
 };
 
 #[derive(Accounts)]
 #[instruction(params: AddInvestorsParams)]
 pub struct AddInvestors<'info> {
     #[account(
-        seeds = [SETTINGS_SEED.as_bytes()],
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
         bump
     )]
     pub settings: Account<'info, Settings>,
     #[account(mut,
-        seeds = [AGREEMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
-    #[account(address = settings.owner @ ErrorCode::NotAuthorized)]
+    #[account(
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), RoleKind::Operator.seed().as_ref(), payer.key().as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Option<Account<'info, Role>>,
+    #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
@@ -37,6 +41,15 @@ pub struct InvestorAllocation {
     pub amount: u64,
     pub token_amount: u64,
     pub wallet: Pubkey,
+    /// Referrer credited with the allocation, `None` when the investor was not referred.
+    pub referral: Option<ReferralTerms>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ReferralTerms {
+    pub referrer: Pubkey,
+    /// Share of the investor's token commission paid to the referrer.
+    pub basis_points: u16,
 }
 
 impl<'info> AddInvestors<'info> {
@@ -44,14 +57,36 @@ impl<'info> AddInvestors<'info> {
         ctx: &mut Context<'_, '_, 'info, 'info, Self>,
         params: AddInvestorsParams,
     ) -> Result<()> {
+        if ctx.accounts.operator.is_none() && ctx.accounts.settings.owner != *ctx.accounts.payer.key
+        {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+
         let agreement = &mut ctx.accounts.agreement;
         let remaining_accounts = ctx.remaining_accounts;
 
-        if ctx.remaining_accounts.len() != params.allocations.len() {
+        // Investor PDAs come first, then the referrer PDA of each referred allocation in order
+        let referrals_count = params
+            .allocations
+            .iter()
+            .filter(|allocation| allocation.referral.is_some())
+            .count();
+        if ctx.remaining_accounts.len() != params.allocations.len() + referrals_count {
             return Err(ErrorCode::InvalidRemainingAccounts.into());
         }
+        let (investor_accounts, referrer_accounts) =
+            remaining_accounts.split_at(params.allocations.len());
+        let mut referrer_accounts = referrer_accounts.iter();
 
-        if agreement.seller_deposited {
+        if agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if agreement.commit_deadline != 0 {
+            return Err(ErrorCode::ProRataAllocation.into());
+        }
+        // After the seller deposit only allocations freed by `investor_withdraw_commitment`
+        // are left to assign, and only until funding completes
+        if agreement.seller_deposited && agreement.hold_duration_start != 0 {
             return Err(ErrorCode::SellerDeposited.into());
         }
 
@@ -73,9 +108,11 @@ impl<'info> AddInvestors<'info> {
 
         // Process each investor
         // TODO: Check number of allocations
+        let now = Clock::get()?.unix_timestamp;
+        let tenant_seed = agreement.tenant_seed();
         let mut seen = std::collections::HashSet::new();
         for (i, allocation) in params.allocations.iter().enumerate() {
-            let investor_account = &remaining_accounts[i];
+            let investor_account = &investor_accounts[i];
 
             if !seen.insert(allocation.wallet) {
                 return Err(ErrorCode::DuplicateInvestor.into());
@@ -111,7 +148,17 @@ impl<'info> AddInvestors<'info> {
                 return Err(ErrorCode::InvalidAmount.into());
             }
 
-            let investor_len = 8 + size_of::<Investor>();
+            if let Some(referral) = &allocation.referral {
+                if referral.basis_points == 0
+                    || referral.basis_points as u64 > BASIC_POINTS
+                    || referral.referrer == Pubkey::default()
+                    || referral.referrer == allocation.wallet
+                {
+                    return Err(ErrorCode::InvalidReferral.into());
+                }
+            }
+
+            let investor_len = Investor::SPACE;
 
             create_account(
                 CpiContext::new_with_signer(
@@ -133,17 +180,82 @@ impl<'info> AddInvestors<'info> {
             )?;
 
             // Initialize investor account
-            let mut investor = Investor::default();
-            investor.agreement = agreement.key();
-            investor.wallet = allocation.wallet;
-            investor.token_allocation = allocation.token_amount;
-            investor.required_amount = allocation.amount;
-            investor.has_withdrawn_tokens = false;
-            investor.investor_deposited = false;
-            investor.bump = bump;
+            let investor = Investor {
+                agreement: agreement.key(),
+                wallet: allocation.wallet,
+                token_allocation: allocation.token_amount,
+                required_amount: allocation.amount,
+                has_withdrawn_tokens: false,
+                investor_deposited: false,
+                bump,
+                version: Investor::VERSION,
+                rent_payer: *ctx.accounts.payer.key,
+                deposit_deadline: if agreement.deposit_window > 0 {
+                    now + agreement.deposit_window
+                } else {
+                    0
+                },
+                referrer: allocation
+                    .referral
+                    .as_ref()
+                    .map(|referral| referral.referrer)
+                    .unwrap_or_default(),
+                referral_bps: allocation
+                    .referral
+                    .as_ref()
+                    .map(|referral| referral.basis_points)
+                    .unwrap_or_default(),
+                ..Default::default()
+            };
 
             investor.try_serialize(&mut *investor_account.try_borrow_mut_data()?)?;
 
+            if let Some(referral) = &allocation.referral {
+                let referrer_account = referrer_accounts
+                    .next()
+                    .ok_or(ErrorCode::InvalidRemainingAccounts)?;
+                let (referrer_pda, referrer_bump) = Pubkey::find_program_address(
+                    &[
+                        REFERRER_SEED.as_bytes(),
+                        tenant_seed.as_ref(),
+                        referral.referrer.as_ref(),
+                    ],
+                    ctx.program_id,
+                );
+                if referrer_pda != referrer_account.key() {
+                    return Err(ErrorCode::InvalidReferrerAccount.into());
+                }
+
+                // The first referral registers the referrer
+                if *referrer_account.owner == system_program::ID {
+                    create_pda_account(
+                        referrer_account,
+                        Referrer::SPACE,
+                        ctx.program_id,
+                        &[
+                            REFERRER_SEED.as_bytes(),
+                            tenant_seed.as_ref(),
+                            referral.referrer.as_ref(),
+                            &[referrer_bump],
+                        ],
+                        &ctx.accounts.payer.to_account_info(),
+                        &ctx.accounts.system_program.to_account_info(),
+                    )?;
+                    Referrer {
+                        tenant_id: agreement.tenant_id,
+                        wallet: referral.referrer,
+                        bump: referrer_bump,
+                        version: Referrer::VERSION,
+                        ..Default::default()
+                    }
+                    .try_serialize(&mut *referrer_account.try_borrow_mut_data()?)?;
+                }
+
+                let mut referrer = Account::<Referrer>::try_from(referrer_account)?;
+                referrer.referred_count += 1;
+                referrer.exit(ctx.program_id)?;
+            }
+
             agreement.investors_count += 1;
             agreement.total_required += allocation.amount;
             agreement.total_token_allocation += allocation.token_amount;
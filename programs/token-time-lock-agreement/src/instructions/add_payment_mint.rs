@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    error::ErrorCode, AdminAction, Agreement, PaymentMint, PaymentMintAdded, PriceFeed, Proposal,
+    RevenuePayout, Role, RoleKind, Settings, AGREEMENT_SEED, BASIC_POINTS, PAYMENT_MINT_SEED,
+    PAYMENT_SEED, PROPOSAL_SEED, ROLE_SEED, SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: AddPaymentMintParams)]
+pub struct AddPaymentMint<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(
+        init,
+        payer = payer,
+        space = PaymentMint::SPACE,
+        seeds = [PAYMENT_MINT_SEED.as_bytes(), agreement.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub payment_mint: Account<'info, PaymentMint>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint,
+        token::authority = agreement,
+        seeds = [PAYMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub payment_token_account: Account<'info, TokenAccount>,
+    /// CHECK: `PriceFeed` layout, required with oracle terms.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    #[account(
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), RoleKind::Operator.seed().as_ref(), payer.key().as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Option<Account<'info, Role>>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AddPaymentMintParams {
+    /// `payment_token_mint` units per `PRICE_SCALE` units of the new mint, `PRICE_SCALE` for 1:1.
+    /// Must be 0 when the mint is oracle priced.
+    pub rate: u64,
+    pub oracle: Option<OracleTerms>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct OracleTerms {
+    pub max_price_age: i64,
+    pub max_confidence_bps: u16,
+}
+
+impl<'info> AddPaymentMint<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: AddPaymentMintParams,
+    ) -> Result<()> {
+        if ctx.accounts.settings.multisig_enabled() {
+            let action = AdminAction::AddPaymentMint {
+                agreement: ctx.accounts.agreement.key(),
+                mint: ctx.accounts.mint.key(),
+                params: params.clone(),
+            };
+            ctx.accounts
+                .proposal
+                .as_mut()
+                .ok_or(ErrorCode::ProposalRequired)?
+                .execute(
+                    &ctx.accounts.settings,
+                    &action,
+                    Clock::get()?.unix_timestamp,
+                )?;
+        } else if ctx.accounts.operator.is_none()
+            && ctx.accounts.settings.owner != *ctx.accounts.payer.key
+        {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+        let agreement = &ctx.accounts.agreement;
+        if agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if agreement.hold_duration_start != 0 {
+            return Err(ErrorCode::FundingCompleted.into());
+        }
+        if agreement.terms_signed() {
+            return Err(ErrorCode::AgreementAlreadyAccepted.into());
+        }
+        // Milestone releases are tracked in `payment_token_mint` only
+        if agreement.milestones_count > 0 {
+            return Err(ErrorCode::InvalidPaymentMint.into());
+        }
+        let oracle = match &params.oracle {
+            Some(terms) => {
+                if params.rate != 0 {
+                    return Err(ErrorCode::InvalidConversionRate.into());
+                }
+                if terms.max_price_age <= 0
+                    || terms.max_confidence_bps == 0
+                    || terms.max_confidence_bps as u64 > BASIC_POINTS
+                {
+                    return Err(ErrorCode::InvalidOracleTerms.into());
+                }
+                let price_feed = ctx
+                    .accounts
+                    .price_feed
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidPriceFeed)?;
+                PriceFeed::load(price_feed)?;
+                price_feed.key()
+            }
+            None => {
+                if params.rate == 0 {
+                    return Err(ErrorCode::InvalidConversionRate.into());
+                }
+                Pubkey::default()
+            }
+        };
+        let mint = ctx.accounts.mint.key();
+        if mint == agreement.payment_token_mint || mint == agreement.project_token_mint {
+            return Err(ErrorCode::InvalidPaymentMint.into());
+        }
+        if !ctx.accounts.settings.allowed_mints.is_empty()
+            && !ctx.accounts.settings.allowed_mints.contains(&mint)
+        {
+            return Err(ErrorCode::PaymentMintNotAllowed.into());
+        }
+
+        let payment_mint = &mut ctx.accounts.payment_mint;
+        payment_mint.agreement = agreement.key();
+        payment_mint.mint = mint;
+        payment_mint.rate = params.rate;
+        payment_mint.oracle = oracle;
+        if let Some(terms) = params.oracle {
+            payment_mint.max_price_age = terms.max_price_age;
+            payment_mint.max_confidence_bps = terms.max_confidence_bps;
+        }
+        payment_mint.revenue_split =
+            vec![RevenuePayout::default(); ctx.accounts.agreement.revenue_split.len()];
+        payment_mint.fees_collected = vec![0; ctx.accounts.agreement.fee_split.len()];
+        payment_mint.bump = ctx.bumps.payment_mint;
+        ctx.accounts.agreement.payment_mints_count += 1;
+        payment_mint.version = PaymentMint::VERSION;
+
+        emit!(PaymentMintAdded {
+            agreement: payment_mint.agreement,
+            mint,
+            rate: params.rate,
+            oracle,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, Agreement, BlockedWallet, Role, RoleKind, Settings, WaitlistEntry,
+    WaitlistJoined, AGREEMENT_SEED, BLOCKLIST_SEED, INVESTOR_SEED, ROLE_SEED, SETTINGS_SEED,
+    WAITLIST_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: AddToWaitlistParams)]
+pub struct AddToWaitlist<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    /// One entry per wallet, a wallet already queued fails to initialize it again.
+    #[account(
+        init,
+        payer = payer,
+        space = WaitlistEntry::SPACE,
+        seeds = [WAITLIST_SEED.as_bytes(), agreement.key().as_ref(), params.wallet.as_ref()],
+        bump
+    )]
+    pub waitlist_entry: Account<'info, WaitlistEntry>,
+    /// CHECK: Investor PDA of the wallet, which must not hold an allocation yet.
+    #[account(
+        seeds = [INVESTOR_SEED.as_bytes(), agreement.key().as_ref(), params.wallet.as_ref()],
+        bump,
+    )]
+    pub investor: UncheckedAccount<'info>,
+    /// CHECK: Blocklist PDA of the wallet, blocked wallets are not queued.
+    #[account(
+        seeds = [BLOCKLIST_SEED.as_bytes(), agreement.tenant_seed().as_ref(), params.wallet.as_ref()],
+        bump,
+    )]
+    pub blocked_wallet: UncheckedAccount<'info>,
+    #[account(
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), RoleKind::Operator.seed().as_ref(), payer.key().as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Option<Account<'info, Role>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AddToWaitlistParams {
+    pub wallet: Pubkey,
+}
+
+impl<'info> AddToWaitlist<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: AddToWaitlistParams,
+    ) -> Result<()> {
+        if ctx.accounts.operator.is_none() && ctx.accounts.settings.owner != *ctx.accounts.payer.key
+        {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+        if ctx.accounts.agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+            return Err(ErrorCode::WalletBlocked.into());
+        }
+        if ctx.accounts.investor.owner == ctx.program_id {
+            return Err(ErrorCode::InvestorAlreadyExists.into());
+        }
+
+        let agreement = &mut ctx.accounts.agreement;
+        let waitlist_entry = &mut ctx.accounts.waitlist_entry;
+        waitlist_entry.agreement = agreement.key();
+        waitlist_entry.wallet = params.wallet;
+        waitlist_entry.position = agreement.waitlist_tail;
+        waitlist_entry.bump = ctx.bumps.waitlist_entry;
+        waitlist_entry.version = WaitlistEntry::VERSION;
+
+        agreement.waitlist_tail += 1;
+
+        emit!(WaitlistJoined {
+            agreement: waitlist_entry.agreement,
+            wallet: waitlist_entry.wallet,
+            position: waitlist_entry.position,
+        });
+
+        Ok(())
+    }
+}
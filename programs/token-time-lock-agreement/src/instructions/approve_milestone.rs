@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, Agreement, MilestoneApproved, MilestonePlan, AGREEMENT_SEED, MILESTONES_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: ApproveMilestoneParams)]
+pub struct ApproveMilestone<'info> {
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(mut,
+        seeds = [MILESTONES_SEED.as_bytes(), agreement.key().as_ref()],
+        bump = milestone_plan.bump,
+    )]
+    pub milestone_plan: Account<'info, MilestonePlan>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ApproveMilestoneParams {
+    pub index: u8,
+}
+
+impl<'info> ApproveMilestone<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: ApproveMilestoneParams,
+    ) -> Result<()> {
+        let agreement = &mut ctx.accounts.agreement;
+        if agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if agreement.raised_amount().is_none() {
+            return Err(ErrorCode::FullAmountNotCollected.into());
+        }
+        let milestone = ctx
+            .accounts
+            .milestone_plan
+            .milestones
+            .get_mut(params.index as usize)
+            .ok_or(ErrorCode::InvalidMilestones)?;
+        if milestone.approver != *ctx.accounts.payer.key {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+        if milestone.approved {
+            return Err(ErrorCode::MilestoneAlreadyApproved.into());
+        }
+
+        milestone.approved = true;
+        agreement.approved_bps += milestone.basis_points;
+
+        emit!(MilestoneApproved {
+            agreement: agreement.key(),
+            index: params.index,
+            approved_bps: agreement.approved_bps,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Proposal, ProposalApproved, Settings, PROPOSAL_SEED, SETTINGS_SEED};
+
+#[derive(Accounts)]
+#[instruction(params: ApproveProposalParams)]
+pub struct ApproveProposal<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ApproveProposalParams;
+
+impl<'info> ApproveProposal<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: ApproveProposalParams,
+    ) -> Result<()> {
+        let approver = *ctx.accounts.payer.key;
+        let proposal = &mut ctx.accounts.proposal;
+
+        if !ctx.accounts.settings.multisig_signers.contains(&approver) {
+            return Err(ErrorCode::NotMultisigSigner.into());
+        }
+        if proposal.executed {
+            return Err(ErrorCode::ProposalAlreadyExecuted.into());
+        }
+        if Clock::get()?.unix_timestamp >= proposal.expires_at {
+            return Err(ErrorCode::ProposalExpired.into());
+        }
+        if proposal.approvals.contains(&approver) {
+            return Err(ErrorCode::ProposalAlreadyApproved.into());
+        }
+
+        proposal.approvals.push(approver);
+
+        emit!(ProposalApproved {
+            proposal: proposal.key(),
+            approver,
+            approvals: proposal.approvals.len() as u8,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    AdminAction, BlockedWallet, Proposal, Settings, WalletBlocked, BLOCKLIST_SEED, PROPOSAL_SEED,
+    SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: BlockWalletParams)]
+pub struct BlockWallet<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(
+        init,
+        payer = payer,
+        space = BlockedWallet::SPACE,
+        seeds = [BLOCKLIST_SEED.as_bytes(), settings.tenant_seed().as_ref(), params.wallet.as_ref()],
+        bump
+    )]
+    pub blocked_wallet: Account<'info, BlockedWallet>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct BlockWalletParams {
+    pub wallet: Pubkey,
+}
+
+impl<'info> BlockWallet<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: BlockWalletParams,
+    ) -> Result<()> {
+        AdminAction::BlockWallet {
+            params: params.clone(),
+        }
+        .authorize(
+            &ctx.accounts.settings,
+            ctx.accounts.proposal.as_mut(),
+            ctx.accounts.payer.key,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let blocked_wallet = &mut ctx.accounts.blocked_wallet;
+        blocked_wallet.tenant_id = ctx.accounts.settings.tenant_id;
+        blocked_wallet.wallet = params.wallet;
+        blocked_wallet.blocked_at = Clock::get()?.unix_timestamp;
+        blocked_wallet.bump = ctx.bumps.blocked_wallet;
+        blocked_wallet.version = BlockedWallet::VERSION;
+
+        emit!(WalletBlocked {
+            settings: ctx.accounts.settings.key(),
+            wallet: params.wallet,
+        });
+
+        Ok(())
+    }
+}
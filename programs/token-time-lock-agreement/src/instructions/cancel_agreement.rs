@@ -2,25 +2,25 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::TokenAccount;
 
 use crate::{
-    error::ErrorCode, Agreement, Investor, Settings, AGREEMENT_SEED, INVESTOR_SEED, PROJECT_SEED,
-    SETTINGS_SEED,
+    error::ErrorCode, AdminAction, Agreement, AgreementTerminated, Investor, Proposal, Settings,
+    AGREEMENT_SEED, BASIC_POINTS, INVESTOR_SEED, PROJECT_SEED, PROPOSAL_SEED, SETTINGS_SEED,
 };
 
 #[derive(Accounts)]
 #[instruction(params: CancelAgreementParams)]
 pub struct CancelAgreement<'info> {
     #[account(
-        seeds = [SETTINGS_SEED.as_bytes()],
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
         bump
     )]
     pub settings: Account<'info, Settings>,
     #[account(mut,
-        seeds = [AGREEMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
     #[account(mut,
-        seeds = [PROJECT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [PROJECT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump,
     )]
     pub project_token_account: Account<'info, TokenAccount>,
@@ -29,6 +29,11 @@ pub struct CancelAgreement<'info> {
         bump,
     )]
     pub investor: Option<Account<'info, Investor>>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub clock: Sysvar<'info, Clock>,
@@ -46,10 +51,21 @@ impl<'info> CancelAgreement<'info> {
             if investor.wallet != *ctx.accounts.payer.key {
                 return Err(ErrorCode::NotAuthorized.into());
             }
-        } else {
-            if ctx.accounts.agreement.token_seller != *ctx.accounts.payer.key
-                && ctx.accounts.agreement.owner != *ctx.accounts.payer.key
-            {
+        } else if ctx.accounts.agreement.token_seller != *ctx.accounts.payer.key {
+            if ctx.accounts.settings.multisig_enabled() {
+                let action = AdminAction::CancelAgreement {
+                    agreement: ctx.accounts.agreement.key(),
+                };
+                ctx.accounts
+                    .proposal
+                    .as_mut()
+                    .ok_or(ErrorCode::ProposalRequired)?
+                    .execute(
+                        &ctx.accounts.settings,
+                        &action,
+                        ctx.accounts.clock.unix_timestamp,
+                    )?;
+            } else if ctx.accounts.agreement.owner != *ctx.accounts.payer.key {
                 return Err(ErrorCode::NotAuthorized.into());
             }
         }
@@ -61,7 +77,58 @@ impl<'info> CancelAgreement<'info> {
 
         // Tokens present but not processed - can cancel immediately
         if current_balance > 0 && !ctx.accounts.agreement.seller_deposited {
-            ctx.accounts.agreement.agreement_cancelled = true;
+            ctx.accounts
+                .agreement
+                .cancel(ctx.accounts.clock.unix_timestamp);
+            return Ok(());
+        }
+
+        // Soft cap missed by the deadline - can cancel immediately so everyone is refunded
+        if ctx.accounts.agreement.soft_cap > 0
+            && ctx.accounts.agreement.hold_duration_start == 0
+            && ctx.accounts.clock.unix_timestamp > ctx.accounts.agreement.funding_deadline
+            && ctx.accounts.agreement.total_invested < ctx.accounts.agreement.soft_cap
+        {
+            ctx.accounts
+                .agreement
+                .cancel(ctx.accounts.clock.unix_timestamp);
+            return Ok(());
+        }
+
+        // Commitments short of the target when the commit window closes - can cancel immediately
+        if ctx.accounts.agreement.commit_deadline > 0
+            && ctx.accounts.agreement.hold_duration_start == 0
+            && ctx.accounts.clock.unix_timestamp > ctx.accounts.agreement.commit_deadline
+            && ctx.accounts.agreement.total_committed < ctx.accounts.agreement.expected_payment
+        {
+            ctx.accounts
+                .agreement
+                .cancel(ctx.accounts.clock.unix_timestamp);
+            return Ok(());
+        }
+
+        // Milestone deals can be terminated by the admin during the hold, investors are refunded
+        // the proceeds not yet approved for release and claim the released share of their tokens
+        let agreement = &ctx.accounts.agreement;
+        if agreement.milestones_count > 0
+            && agreement.hold_duration_start > 0
+            && ctx.accounts.clock.unix_timestamp
+                < agreement.hold_duration_start + agreement.hold_duration
+            && (agreement.approved_bps as u64) < BASIC_POINTS
+            && ctx.accounts.investor.is_none()
+            && agreement.token_seller != *ctx.accounts.payer.key
+        {
+            let tokens_owed = (agreement.tokens_sold as u128 * agreement.approved_bps as u128
+                / BASIC_POINTS as u128) as u64;
+            ctx.accounts
+                .agreement
+                .cancel(ctx.accounts.clock.unix_timestamp);
+            ctx.accounts.agreement.terminated = true;
+            ctx.accounts.agreement.tokens_owed = tokens_owed;
+            emit!(AgreementTerminated {
+                agreement: ctx.accounts.agreement.key(),
+                approved_bps: ctx.accounts.agreement.approved_bps,
+            });
             return Ok(());
         }
 
@@ -75,15 +142,16 @@ impl<'info> CancelAgreement<'info> {
             {
                 return Err(ErrorCode::CancellationTimeoutNotReached.into());
             }
-            if ctx.accounts.agreement.total_invested == ctx.accounts.agreement.expected_payment
-                && ctx.accounts.agreement.hold_duration_start > 0
-            {
+            // Funding completed, including soft cap agreements finalized below the hard cap
+            if ctx.accounts.agreement.hold_duration_start > 0 {
                 return Err(ErrorCode::FullAmountNotCollected.into());
             }
-            ctx.accounts.agreement.agreement_cancelled = true;
+            ctx.accounts
+                .agreement
+                .cancel(ctx.accounts.clock.unix_timestamp);
             return Ok(());
         }
 
-        return Err(ErrorCode::TokensNotDeposited.into());
+        Err(ErrorCode::TokensNotDeposited.into())
     }
 }
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+use crate::{
+    error::ErrorCode, Agreement, BlockedWallet, BondShareClaimed, Investor, AGREEMENT_SEED,
+    BLOCKLIST_SEED, BOND_SEED, INVESTOR_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: ClaimBondShareParams)]
+pub struct ClaimBondShare<'info> {
+    #[account(
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(mut,
+        seeds = [INVESTOR_SEED.as_bytes(), agreement.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub investor: Account<'info, Investor>,
+    /// CHECK: Blocklist PDA of the investor.
+    #[account(
+        seeds = [BLOCKLIST_SEED.as_bytes(), agreement.tenant_seed().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub blocked_wallet: UncheckedAccount<'info>,
+    #[account(mut,
+        seeds = [BOND_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub bond_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub investor_token_account: Account<'info, TokenAccount>,
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ClaimBondShareParams;
+
+impl<'info> ClaimBondShare<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: ClaimBondShareParams,
+    ) -> Result<()> {
+        let agreement = &ctx.accounts.agreement;
+        if !agreement.bond_slashed || agreement.bond_slash_base == 0 {
+            return Err(ErrorCode::BondNotSlashedToInvestors.into());
+        }
+        // Investors refunded since the slash were still counted in `bond_slash_base`
+        let investor = &mut ctx.accounts.investor;
+        if !(investor.investor_deposited || investor.refunded) || investor.bond_share_basis() == 0 {
+            return Err(ErrorCode::NotAnInvestor.into());
+        }
+        if investor.bond_claimed {
+            return Err(ErrorCode::BondShareAlreadyClaimed.into());
+        }
+        if Clock::get()?.unix_timestamp > agreement.bond_slashed_at + agreement.bond_claim_window {
+            return Err(ErrorCode::BondClaimWindowClosed.into());
+        }
+        if BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+            return Err(ErrorCode::WalletBlocked.into());
+        }
+        if ctx.accounts.investor_token_account.owner != investor.wallet
+            || ctx.accounts.investor_token_account.mint != agreement.payment_token_mint
+        {
+            return Err(ErrorCode::InvalidDestination.into());
+        }
+
+        let amount = (agreement.seller_bond as u128 * investor.bond_share_basis() as u128
+            / agreement.bond_slash_base as u128) as u64;
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.bond_token_account.to_account_info(),
+                    to: ctx.accounts.investor_token_account.to_account_info(),
+                    authority: agreement.to_account_info(),
+                },
+                &[&[
+                    AGREEMENT_SEED.as_bytes(),
+                    agreement.tenant_seed().as_ref(),
+                    agreement.agreement_id.to_le_bytes().as_ref(),
+                    &[agreement.bump],
+                ]],
+            ),
+            amount,
+        )?;
+        investor.bond_claimed = true;
+
+        emit!(BondShareClaimed {
+            agreement: agreement.key(),
+            wallet: investor.wallet,
+            amount,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+use crate::{
+    error::ErrorCode, utils::create_pda_account, Acknowledgement, Agreement, Attestation,
+    BlockedWallet, FundsCommitted, Investor, ACKNOWLEDGEMENT_SEED, AGREEMENT_SEED,
+    ATTESTATION_SEED, INVESTOR_SEED, PAYMENT_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: CommitFundsParams)]
+pub struct CommitFunds<'info> {
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    /// CHECK: Investor PDA of the committer, created on the first commitment.
+    #[account(mut,
+        seeds = [INVESTOR_SEED.as_bytes(), agreement.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub investor: UncheckedAccount<'info>,
+    #[account(
+        seeds = [ACKNOWLEDGEMENT_SEED.as_bytes(), agreement.key().as_ref(), payer.key().as_ref()],
+        bump = acknowledgement.bump,
+    )]
+    pub acknowledgement: Option<Account<'info, Acknowledgement>>,
+    #[account(
+        seeds = [ATTESTATION_SEED.as_bytes(), agreement.tenant_seed().as_ref(), payer.key().as_ref()],
+        bump = attestation.bump,
+    )]
+    pub attestation: Option<Account<'info, Attestation>>,
+    /// CHECK: Blocklist PDA of the investor wallet, checked in `handle`.
+    pub blocked_wallet: UncheckedAccount<'info>,
+    #[account(mut,
+        seeds = [PAYMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub payment_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct CommitFundsParams {
+    pub amount: u64,
+}
+
+impl<'info> CommitFunds<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: CommitFundsParams,
+    ) -> Result<()> {
+        let agreement = &ctx.accounts.agreement;
+        let now = ctx.accounts.clock.unix_timestamp;
+        if agreement.commit_deadline == 0 {
+            return Err(ErrorCode::CommitWindowNotEnabled.into());
+        }
+        if agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if !agreement.seller_deposited {
+            return Err(ErrorCode::SellerMustDepositFirst.into());
+        }
+        if !agreement.accepted() {
+            return Err(ErrorCode::AgreementNotAccepted.into());
+        }
+        if now > agreement.commit_deadline {
+            return Err(ErrorCode::CommitWindowClosed.into());
+        }
+        if params.amount == 0 {
+            return Err(ErrorCode::InvalidAmount.into());
+        }
+        if agreement.token_seller == *ctx.accounts.payer.key {
+            return Err(ErrorCode::SellerCannotDepositStablecoins.into());
+        }
+        if agreement.compliance_required {
+            ctx.accounts
+                .attestation
+                .as_ref()
+                .ok_or(ErrorCode::AttestationRequired)?
+                .verify(agreement.tenant_id, ctx.accounts.payer.key, now)?;
+        }
+        if agreement.document_hash != [0; 32] {
+            match &ctx.accounts.acknowledgement {
+                Some(acknowledgement)
+                    if acknowledgement.document_hash == agreement.document_hash => {}
+                _ => return Err(ErrorCode::DocumentNotAcknowledged.into()),
+            }
+        }
+
+        let investor_account = ctx.accounts.investor.to_account_info();
+        let mut investor = if *investor_account.owner == system_program::ID {
+            create_pda_account(
+                &investor_account,
+                Investor::SPACE,
+                ctx.program_id,
+                &[
+                    INVESTOR_SEED.as_bytes(),
+                    agreement.key().as_ref(),
+                    ctx.accounts.payer.key.as_ref(),
+                    &[ctx.bumps.investor],
+                ],
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+            ctx.accounts.agreement.investors_count += 1;
+
+            Investor {
+                agreement: ctx.accounts.agreement.key(),
+                wallet: *ctx.accounts.payer.key,
+                investor_deposited: true,
+                bump: ctx.bumps.investor,
+                version: Investor::VERSION,
+                rent_payer: *ctx.accounts.payer.key,
+                ..Default::default()
+            }
+        } else {
+            if investor_account.owner != ctx.program_id {
+                return Err(ErrorCode::InvalidInvestorAccountOwner.into());
+            }
+            Investor::try_deserialize(&mut &investor_account.data.borrow()[..])?
+        };
+        // Settlement refunds and tokens go to the investor wallet
+        if BlockedWallet::is_blocked(
+            &ctx.accounts.blocked_wallet,
+            &ctx.accounts.agreement.tenant_seed(),
+            &investor.wallet,
+        )? {
+            return Err(ErrorCode::WalletBlocked.into());
+        }
+
+        // Commitments sit in `required_amount` until settlement replaces it with the pro rata share,
+        // so cancellation refunds them in full
+        investor.required_amount += params.amount;
+        // Deposits made after the slash do not dilute the bond shares of earlier investors
+        if ctx.accounts.agreement.bond_slashed {
+            investor.deposited_after_slash += params.amount;
+        }
+        investor.try_serialize(&mut *investor_account.try_borrow_mut_data()?)?;
+
+        let agreement = &mut ctx.accounts.agreement;
+        agreement.total_committed += params.amount;
+        agreement.total_required += params.amount;
+        agreement.total_invested += params.amount;
+
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer_token_account.to_account_info(),
+                    to: ctx.accounts.payment_token_account.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            params.amount,
+        )?;
+
+        emit!(FundsCommitted {
+            agreement: agreement.key(),
+            wallet: *ctx.accounts.payer.key,
+            amount: params.amount,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, AdminAction, Proposal, ProposalCreated, Settings, PROPOSAL_SEED,
+    SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: CreateProposalParams)]
+pub struct CreateProposal<'info> {
+    #[account(mut,
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(
+        init,
+        payer = payer,
+        space = Proposal::SPACE,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), settings.next_proposal_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct CreateProposalParams {
+    pub action: AdminAction,
+    pub expires_at: i64,
+}
+
+impl<'info> CreateProposal<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: CreateProposalParams,
+    ) -> Result<()> {
+        if !ctx
+            .accounts
+            .settings
+            .multisig_signers
+            .contains(ctx.accounts.payer.key)
+        {
+            return Err(ErrorCode::NotMultisigSigner.into());
+        }
+        if params.expires_at <= Clock::get()?.unix_timestamp {
+            return Err(ErrorCode::ProposalExpired.into());
+        }
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.settings = ctx.accounts.settings.key();
+        proposal.proposal_id = ctx.accounts.settings.next_proposal_id;
+        proposal.proposer = *ctx.accounts.payer.key;
+        proposal.action_hash = params.action.hash()?;
+        // the proposer approves by proposing
+        proposal.approvals = vec![*ctx.accounts.payer.key];
+        proposal.expires_at = params.expires_at;
+        proposal.bump = ctx.bumps.proposal;
+        proposal.version = Proposal::VERSION;
+
+        ctx.accounts.settings.next_proposal_id += 1;
+
+        emit!(ProposalCreated {
+            proposal: proposal.key(),
+            proposer: proposal.proposer,
+            action: params.action,
+            expires_at: params.expires_at,
+        });
+
+        Ok(())
+    }
+}
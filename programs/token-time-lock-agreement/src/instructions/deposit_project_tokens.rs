@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+use crate::{
+    error::ErrorCode, utils::return_seller_bond, Agreement, ProjectTokensDeposited, AGREEMENT_SEED,
+    BOND_SEED, PROJECT_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: DepositProjectTokensParams)]
+pub struct DepositProjectTokens<'info> {
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(mut,
+        seeds = [PROJECT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub project_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub company_token_account: Account<'info, TokenAccount>,
+    #[account(mut,
+        seeds = [BOND_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub bond_token_account: Option<Account<'info, TokenAccount>>,
+    /// Receives the seller bond back when tokens arrive before `delivery_deadline`.
+    #[account(mut)]
+    pub seller_payment_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct DepositProjectTokensParams;
+
+impl<'info> DepositProjectTokens<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: DepositProjectTokensParams,
+    ) -> Result<()> {
+        if ctx.accounts.agreement.token_seller != *ctx.accounts.payer.key {
+            return Err(ErrorCode::OnlySellerAllowed.into());
+        }
+        if ctx.accounts.agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if ctx.accounts.agreement.seller_deposited {
+            return Err(ErrorCode::TokensAlreadyDeposited.into());
+        }
+        if !ctx.accounts.agreement.accepted() {
+            return Err(ErrorCode::AgreementNotAccepted.into());
+        }
+        if ctx.accounts.company_token_account.mint != ctx.accounts.agreement.project_token_mint
+            || ctx.accounts.company_token_account.owner != ctx.accounts.agreement.company_wallet
+        {
+            return Err(ErrorCode::InvalidDestination.into());
+        }
+
+        // tokens sent to the vault by anyone else go to the company token account
+        let excess_tokens = ctx.accounts.project_token_account.amount;
+        if excess_tokens > 0 {
+            anchor_spl::token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.project_token_account.to_account_info(),
+                        to: ctx.accounts.company_token_account.to_account_info(),
+                        authority: ctx.accounts.agreement.to_account_info(),
+                    },
+                    &[&[
+                        AGREEMENT_SEED.as_bytes(),
+                        ctx.accounts.agreement.tenant_seed().as_ref(),
+                        ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
+                        &[ctx.accounts.agreement.bump],
+                    ]],
+                ),
+                excess_tokens,
+            )?;
+        }
+
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.seller_token_account.to_account_info(),
+                    to: ctx.accounts.project_token_account.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            ctx.accounts.agreement.expected_tokens,
+        )?;
+
+        let agreement = &mut ctx.accounts.agreement;
+        agreement.token_depositor = *ctx.accounts.payer.key;
+        agreement.deposited_tokens = agreement.expected_tokens;
+        agreement.seller_deposited = true;
+        agreement.first_deposit_time = ctx.accounts.clock.unix_timestamp;
+
+        emit!(ProjectTokensDeposited {
+            agreement: agreement.key(),
+            depositor: agreement.token_depositor,
+            amount: agreement.deposited_tokens,
+        });
+
+        if ctx
+            .accounts
+            .agreement
+            .bond_returnable(ctx.accounts.clock.unix_timestamp)
+        {
+            return_seller_bond(
+                &mut ctx.accounts.agreement,
+                ctx.accounts.bond_token_account.as_ref(),
+                ctx.accounts.seller_payment_token_account.as_ref(),
+                &ctx.accounts.token_program.to_account_info(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
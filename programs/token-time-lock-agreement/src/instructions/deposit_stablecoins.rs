@@ -1,13 +1,18 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount, Transfer};
 
-use crate::{error::ErrorCode, Agreement, Investor, AGREEMENT_SEED, INVESTOR_SEED, PAYMENT_SEED};
+use crate::{
+    error::ErrorCode, utils::wrap_native, Acknowledgement, Agreement, Attestation, BlockedWallet,
+    DirectPaymentReceived, Investor, PaymentConverted, PaymentMint, PaymentReceipt,
+    ACKNOWLEDGEMENT_SEED, AGREEMENT_SEED, ATTESTATION_SEED, INVESTOR_SEED, MAX_MEMO_LEN,
+    PAYMENT_MINT_SEED, PAYMENT_RECEIPT_SEED, PAYMENT_SEED,
+};
 
 #[derive(Accounts)]
 #[instruction(params: DepositStablecoinsParams)]
 pub struct DepositStablecoins<'info> {
     #[account(mut,
-        seeds = [AGREEMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
@@ -16,10 +21,40 @@ pub struct DepositStablecoins<'info> {
         bump,
     )]
     pub investor: Option<Account<'info, Investor>>,
+    #[account(
+        seeds = [ACKNOWLEDGEMENT_SEED.as_bytes(), agreement.key().as_ref(), acknowledgement.wallet.as_ref()],
+        bump = acknowledgement.bump,
+    )]
+    pub acknowledgement: Option<Account<'info, Acknowledgement>>,
+    #[account(
+        seeds = [ATTESTATION_SEED.as_bytes(), agreement.tenant_seed().as_ref(), attestation.wallet.as_ref()],
+        bump = attestation.bump,
+    )]
+    pub attestation: Option<Account<'info, Attestation>>,
+    /// CHECK: Blocklist PDA of the investor wallet, or of the payer of a direct payment, checked
+    /// in `handle`.
+    pub blocked_wallet: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = PaymentReceipt::SPACE,
+        seeds = [PAYMENT_RECEIPT_SEED.as_bytes(), agreement.key().as_ref(), payer.key().as_ref(), params.receipt_nonce.unwrap_or_default().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub payment_receipt: Option<Account<'info, PaymentReceipt>>,
+    /// Mint the investor pays in when it is not `payment_token_mint`.
+    #[account(
+        seeds = [PAYMENT_MINT_SEED.as_bytes(), agreement.key().as_ref(), payment_mint.mint.as_ref()],
+        bump = payment_mint.bump,
+    )]
+    pub payment_mint: Option<Account<'info, PaymentMint>>,
+    /// CHECK: Matched against `payment_mint.oracle` when the mint is oracle priced.
+    pub price_feed: Option<UncheckedAccount<'info>>,
     #[account(mut)]
     pub destination_token_account: Account<'info, TokenAccount>,
+    /// `None` pays in lamports, wrapped into `destination_token_account` of the native mint.
     #[account(mut)]
-    pub payer_token_account: Account<'info, TokenAccount>,
+    pub payer_token_account: Option<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -29,7 +64,13 @@ pub struct DepositStablecoins<'info> {
 
 #[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
 pub struct DepositStablecoinsParams {
+    /// Exact deposit, or the most the investor pays in an oracle-priced mint.
     pub amount: u64,
+    /// Side letter reference kept on the receipt of a direct payment.
+    pub memo: Option<String>,
+    /// Picked by the payer of a direct payment to derive a receipt address of its own, unique
+    /// per payer.
+    pub receipt_nonce: Option<u64>,
 }
 
 impl<'info> DepositStablecoins<'info> {
@@ -43,18 +84,88 @@ impl<'info> DepositStablecoins<'info> {
         if !ctx.accounts.agreement.seller_deposited {
             return Err(ErrorCode::SellerMustDepositFirst.into());
         }
+        if !ctx.accounts.agreement.accepted() {
+            return Err(ErrorCode::AgreementNotAccepted.into());
+        }
         if ctx.accounts.agreement.token_seller == *ctx.accounts.payer.key {
             return Err(ErrorCode::SellerCannotDepositStablecoins.into());
         }
+        // A third party may pay for an investor, the wallet receiving the tokens must not be blocked
+        let wallet = match &ctx.accounts.investor {
+            Some(investor) => investor.wallet,
+            None => *ctx.accounts.payer.key,
+        };
+        if BlockedWallet::is_blocked(
+            &ctx.accounts.blocked_wallet,
+            &ctx.accounts.agreement.tenant_seed(),
+            &wallet,
+        )? {
+            return Err(ErrorCode::WalletBlocked.into());
+        }
+        if ctx.accounts.agreement.compliance_required {
+            ctx.accounts
+                .attestation
+                .as_ref()
+                .ok_or(ErrorCode::AttestationRequired)?
+                .verify(
+                    ctx.accounts.agreement.tenant_id,
+                    &wallet,
+                    ctx.accounts.clock.unix_timestamp,
+                )?;
+        }
 
+        let mut amount = params.amount;
         if let Some(investor) = &mut ctx.accounts.investor {
-            if params.amount != investor.required_amount {
-                return Err(ErrorCode::InvalidAmount.into());
+            if ctx.accounts.payment_receipt.is_some() {
+                return Err(ErrorCode::InvalidPaymentReceipt.into());
+            }
+            let mut rate = 0;
+            match &ctx.accounts.payment_mint {
+                Some(payment_mint) => {
+                    rate = payment_mint.current_rate(
+                        ctx.accounts.price_feed.as_deref(),
+                        ctx.accounts.clock.unix_timestamp,
+                    )?;
+                    amount = PaymentMint::mint_amount(investor.required_amount, rate);
+                    // The price moves between signing and execution, `params.amount` caps it
+                    if payment_mint.oracle != Pubkey::default() {
+                        if params.amount < amount {
+                            return Err(ErrorCode::SlippageExceeded.into());
+                        }
+                    } else if params.amount != amount {
+                        return Err(ErrorCode::InvalidAmount.into());
+                    }
+                }
+                None => {
+                    if params.amount != investor.required_amount {
+                        return Err(ErrorCode::InvalidAmount.into());
+                    }
+                }
             }
             if investor.investor_deposited {
                 return Err(ErrorCode::AlreadyDeposited.into());
             }
-            if ctx.accounts.agreement.total_invested + params.amount
+            if ctx.accounts.agreement.soft_cap > 0
+                && ctx.accounts.clock.unix_timestamp > ctx.accounts.agreement.funding_deadline
+            {
+                return Err(ErrorCode::FundingDeadlinePassed.into());
+            }
+            if investor.deposit_deadline != 0
+                && ctx.accounts.clock.unix_timestamp > investor.deposit_deadline
+            {
+                return Err(ErrorCode::DepositDeadlinePassed.into());
+            }
+            // Agreements without a document hash predate acknowledgements
+            if ctx.accounts.agreement.document_hash != [0; 32] {
+                match &ctx.accounts.acknowledgement {
+                    Some(acknowledgement)
+                        if acknowledgement.wallet == investor.wallet
+                            && acknowledgement.document_hash
+                                == ctx.accounts.agreement.document_hash => {}
+                    _ => return Err(ErrorCode::DocumentNotAcknowledged.into()),
+                }
+            }
+            if ctx.accounts.agreement.total_invested + investor.required_amount
                 > ctx.accounts.agreement.expected_payment
             {
                 return Err(ErrorCode::DepositExceedsExpectedPayment.into());
@@ -63,7 +174,13 @@ impl<'info> DepositStablecoins<'info> {
             let (expected_destination_token_account, _) = Pubkey::find_program_address(
                 &[
                     PAYMENT_SEED.as_bytes(),
+                    ctx.accounts.agreement.tenant_seed().as_ref(),
                     ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
+                    ctx.accounts
+                        .payment_mint
+                        .as_ref()
+                        .map(|payment_mint| payment_mint.mint.as_ref())
+                        .unwrap_or_default(),
                 ],
                 ctx.program_id,
             );
@@ -71,15 +188,38 @@ impl<'info> DepositStablecoins<'info> {
                 return Err(ErrorCode::InvalidDestination.into());
             }
 
-            ctx.accounts.agreement.total_invested += params.amount;
+            ctx.accounts.agreement.total_invested += investor.required_amount;
+            ctx.accounts.agreement.tokens_sold += investor.token_allocation;
+            if let Some(payment_mint) = &ctx.accounts.payment_mint {
+                ctx.accounts.agreement.secondary_invested += investor.required_amount;
+                investor.payment_mint = payment_mint.mint;
+                investor.paid_amount = amount;
+                investor.paid_rate = rate;
+
+                emit!(PaymentConverted {
+                    agreement: ctx.accounts.agreement.key(),
+                    wallet: investor.wallet,
+                    mint: payment_mint.mint,
+                    amount,
+                    rate,
+                    value: investor.required_amount,
+                });
+            }
             if ctx.accounts.agreement.total_invested == ctx.accounts.agreement.expected_payment
                 && ctx.accounts.agreement.hold_duration_start == 0
             {
                 ctx.accounts.agreement.hold_duration_start = ctx.accounts.clock.unix_timestamp;
             }
 
+            // Deposits made after the slash do not dilute the bond shares of earlier investors
+            if ctx.accounts.agreement.bond_slashed {
+                investor.deposited_after_slash = investor.required_amount;
+            }
             investor.investor_deposited = true;
         } else {
+            if ctx.accounts.payment_mint.is_some() {
+                return Err(ErrorCode::InvalidPaymentMint.into());
+            }
             if ctx.accounts.destination_token_account.owner.key()
                 != ctx.accounts.agreement.company_wallet
                 || ctx.accounts.destination_token_account.mint
@@ -87,19 +227,59 @@ impl<'info> DepositStablecoins<'info> {
             {
                 return Err(ErrorCode::InvalidDestination.into());
             }
+            let memo = params.memo.unwrap_or_default();
+            if memo.len() > MAX_MEMO_LEN {
+                return Err(ErrorCode::MemoTooLong.into());
+            }
+
+            let agreement = &mut ctx.accounts.agreement;
+            let receipt = ctx
+                .accounts
+                .payment_receipt
+                .as_mut()
+                .ok_or(ErrorCode::PaymentReceiptRequired)?;
+            receipt.agreement = agreement.key();
+            receipt.receipt_id = agreement.payment_receipts_count;
+            receipt.payer = *ctx.accounts.payer.key;
+            receipt.mint = agreement.payment_token_mint;
+            receipt.amount = params.amount;
+            receipt.paid_at = ctx.accounts.clock.unix_timestamp;
+            receipt.bump = ctx.bumps.payment_receipt.unwrap_or_default();
+            receipt.version = PaymentReceipt::VERSION;
+            receipt.nonce = params.receipt_nonce.unwrap_or_default();
+            receipt.memo = memo;
+
+            agreement.payment_receipts_count += 1;
+            agreement.direct_payments_total += params.amount;
+
+            emit!(DirectPaymentReceived {
+                agreement: agreement.key(),
+                receipt: receipt.key(),
+                payer: receipt.payer,
+                amount: params.amount,
+            });
         }
 
-        anchor_spl::token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.payer_token_account.to_account_info(),
-                    to: ctx.accounts.destination_token_account.to_account_info(),
-                    authority: ctx.accounts.payer.to_account_info(),
-                },
-            ),
-            params.amount,
-        )?;
+        match &ctx.accounts.payer_token_account {
+            Some(payer_token_account) => anchor_spl::token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: payer_token_account.to_account_info(),
+                        to: ctx.accounts.destination_token_account.to_account_info(),
+                        authority: ctx.accounts.payer.to_account_info(),
+                    },
+                ),
+                amount,
+            )?,
+            None => wrap_native(
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.destination_token_account,
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                amount,
+            )?,
+        }
 
         Ok(())
     }
@@ -0,0 +1,178 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, utils::create_pda_account, Agreement, AllocationExpired, Investor, Referrer,
+    Role, RoleKind, Settings, WaitlistEntry, AGREEMENT_SEED, INVESTOR_SEED, REFERRER_SEED,
+    ROLE_SEED, SETTINGS_SEED, WAITLIST_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: ExpireAllocationParams)]
+pub struct ExpireAllocation<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(mut,
+        close = agreement_owner,
+        seeds = [INVESTOR_SEED.as_bytes(), agreement.key().as_ref(), investor.wallet.as_ref()],
+        bump,
+    )]
+    pub investor: Account<'info, Investor>,
+    /// Required when the investor was referred, its referral count is released
+    /// unless a waitlisted wallet takes over the allocation.
+    #[account(mut,
+        seeds = [REFERRER_SEED.as_bytes(), agreement.tenant_seed().as_ref(), investor.referrer.as_ref()],
+        bump = referrer.bump,
+    )]
+    pub referrer: Option<Account<'info, Referrer>>,
+    /// Head of the waitlist, required while the waitlist is not empty.
+    #[account(mut,
+        close = agreement_owner,
+        seeds = [WAITLIST_SEED.as_bytes(), agreement.key().as_ref(), waitlist_entry.wallet.as_ref()],
+        bump = waitlist_entry.bump,
+        constraint = waitlist_entry.position == agreement.waitlist_head @ ErrorCode::InvalidWaitlistEntry,
+    )]
+    pub waitlist_entry: Option<Account<'info, WaitlistEntry>>,
+    /// CHECK: Investor PDA of the waitlisted wallet, created in the handler.
+    #[account(mut)]
+    pub next_investor: Option<UncheckedAccount<'info>>,
+    /// CHECK: Paid the rent of the closed accounts, receives it back.
+    #[account(mut,
+        address = agreement.owner @ ErrorCode::InvalidDestination,
+    )]
+    pub agreement_owner: UncheckedAccount<'info>,
+    #[account(
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), RoleKind::Operator.seed().as_ref(), payer.key().as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Option<Account<'info, Role>>,
+    /// Owner or operator, pays the rent of the waitlisted wallet's investor account.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ExpireAllocationParams;
+
+impl<'info> ExpireAllocation<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: ExpireAllocationParams,
+    ) -> Result<()> {
+        if ctx.accounts.operator.is_none() && ctx.accounts.settings.owner != *ctx.accounts.payer.key
+        {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+        let now = Clock::get()?.unix_timestamp;
+        if ctx.accounts.agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if ctx.accounts.agreement.hold_duration_start != 0 {
+            return Err(ErrorCode::FundingCompleted.into());
+        }
+        if ctx.accounts.investor.investor_deposited {
+            return Err(ErrorCode::AlreadyDeposited.into());
+        }
+        if ctx.accounts.investor.deposit_deadline == 0
+            || now <= ctx.accounts.investor.deposit_deadline
+        {
+            return Err(ErrorCode::DepositDeadlineNotReached.into());
+        }
+
+        let agreement_key = ctx.accounts.agreement.key();
+        let expired = &ctx.accounts.investor;
+        let mut next_wallet = None;
+
+        if ctx.accounts.agreement.waitlist_head < ctx.accounts.agreement.waitlist_tail {
+            let (Some(waitlist_entry), Some(next_investor)) =
+                (&ctx.accounts.waitlist_entry, &ctx.accounts.next_investor)
+            else {
+                return Err(ErrorCode::InvalidWaitlistEntry.into());
+            };
+
+            let (investor_pda, bump) = Pubkey::find_program_address(
+                &[
+                    INVESTOR_SEED.as_bytes(),
+                    agreement_key.as_ref(),
+                    waitlist_entry.wallet.as_ref(),
+                ],
+                ctx.program_id,
+            );
+            if investor_pda != next_investor.key() {
+                return Err(ErrorCode::InvalidInvestorAccount.into());
+            }
+            // A wallet that got an allocation since it joined is skipped with
+            // `skip_waitlist_entry`
+            if next_investor.owner == ctx.program_id {
+                return Err(ErrorCode::InvestorAlreadyExists.into());
+            }
+
+            create_pda_account(
+                &next_investor.to_account_info(),
+                Investor::SPACE,
+                ctx.program_id,
+                &[
+                    INVESTOR_SEED.as_bytes(),
+                    agreement_key.as_ref(),
+                    waitlist_entry.wallet.as_ref(),
+                    &[bump],
+                ],
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+
+            // The waitlisted wallet takes over the allocation and its referral with a fresh
+            // deadline
+            let investor = Investor {
+                agreement: agreement_key,
+                wallet: waitlist_entry.wallet,
+                token_allocation: expired.token_allocation,
+                required_amount: expired.required_amount,
+                referrer: expired.referrer,
+                referral_bps: expired.referral_bps,
+                bump,
+                version: Investor::VERSION,
+                rent_payer: *ctx.accounts.payer.key,
+                deposit_deadline: now + ctx.accounts.agreement.deposit_window,
+                ..Default::default()
+            };
+            investor.try_serialize(&mut *next_investor.try_borrow_mut_data()?)?;
+
+            next_wallet = Some(waitlist_entry.wallet);
+            ctx.accounts.agreement.waitlist_head += 1;
+        } else {
+            // Nobody is waiting, free the allocation for `add_investors`
+            let agreement = &mut ctx.accounts.agreement;
+            agreement.total_required -= expired.required_amount;
+            agreement.total_token_allocation -= expired.token_allocation;
+            agreement.investors_count -= 1;
+            if expired.referrer != Pubkey::default() {
+                let referrer = ctx
+                    .accounts
+                    .referrer
+                    .as_mut()
+                    .ok_or(ErrorCode::InvalidReferrerAccount)?;
+                referrer.referred_count = referrer
+                    .referred_count
+                    .checked_sub(1)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+        }
+
+        emit!(AllocationExpired {
+            agreement: agreement_key,
+            wallet: expired.wallet,
+            next_wallet,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Agreement, FundingFinalized, AGREEMENT_SEED};
+
+#[derive(Accounts)]
+#[instruction(params: FinalizeFundingParams)]
+pub struct FinalizeFunding<'info> {
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    pub payer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct FinalizeFundingParams;
+
+impl<'info> FinalizeFunding<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: FinalizeFundingParams,
+    ) -> Result<()> {
+        let agreement = &mut ctx.accounts.agreement;
+        if agreement.soft_cap == 0 {
+            return Err(ErrorCode::InvalidSoftCap.into());
+        }
+        if agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if agreement.hold_duration_start != 0 {
+            return Err(ErrorCode::FundingCompleted.into());
+        }
+        if ctx.accounts.clock.unix_timestamp <= agreement.funding_deadline {
+            return Err(ErrorCode::FundingDeadlineNotReached.into());
+        }
+        if agreement.total_invested < agreement.soft_cap {
+            return Err(ErrorCode::SoftCapNotReached.into());
+        }
+
+        // The hold starts as if the hard cap had been reached
+        agreement.hold_duration_start = ctx.accounts.clock.unix_timestamp;
+
+        emit!(FundingFinalized {
+            agreement: agreement.key(),
+            raised: agreement.total_invested,
+            tokens_sold: agreement.tokens_sold,
+        });
+
+        Ok(())
+    }
+}
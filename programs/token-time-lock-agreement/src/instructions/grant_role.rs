@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    AdminAction, Proposal, Role, RoleGranted, RoleKind, Settings, PROPOSAL_SEED, ROLE_SEED,
+    SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: GrantRoleParams)]
+pub struct GrantRole<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(
+        init,
+        payer = payer,
+        space = Role::SPACE,
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), params.kind.seed().as_ref(), params.wallet.as_ref()],
+        bump
+    )]
+    pub role: Account<'info, Role>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct GrantRoleParams {
+    pub wallet: Pubkey,
+    pub kind: RoleKind,
+}
+
+impl<'info> GrantRole<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: GrantRoleParams,
+    ) -> Result<()> {
+        AdminAction::GrantRole {
+            params: params.clone(),
+        }
+        .authorize(
+            &ctx.accounts.settings,
+            ctx.accounts.proposal.as_mut(),
+            ctx.accounts.payer.key,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let role = &mut ctx.accounts.role;
+        role.settings = ctx.accounts.settings.key();
+        role.wallet = params.wallet;
+        role.kind = params.kind;
+        role.bump = ctx.bumps.role;
+        role.version = Role::VERSION;
+
+        emit!(RoleGranted {
+            settings: role.settings,
+            wallet: role.wallet,
+            kind: role.kind,
+        });
+
+        Ok(())
+    }
+}
@@ -1,8 +1,9 @@
-use std::mem::size_of;
-
 use anchor_lang::prelude::*;
 
-use crate::{Settings, SETTINGS_SEED};
+use crate::{
+    error::ErrorCode, program::TokenTimeLockAgreement, tenant_seed, Settings, BASIC_POINTS,
+    MAX_ALLOWED_MINTS, SETTINGS_SEED,
+};
 
 #[derive(Accounts)]
 #[instruction(params: InitializeParams)]
@@ -10,11 +11,19 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + size_of::<Settings>(),
-        seeds = [SETTINGS_SEED.as_bytes()],
+        space = Settings::SPACE,
+        seeds = [SETTINGS_SEED.as_bytes(), tenant_seed(params.tenant_id).as_ref()],
         bump
     )]
     pub settings: Account<'info, Settings>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, TokenTimeLockAgreement>,
+    // Only the upgrade authority may create settings, so nobody can front-run the first initialize
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::NotAuthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub authority: Signer<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -25,6 +34,8 @@ pub struct InitializeParams {
     pub cancel_timeout: i64,
     pub commission_basis_points: u16,
     pub owner: Pubkey,
+    pub tenant_id: u64,
+    pub allowed_mints: Vec<Pubkey>,
 }
 
 impl<'info> Initialize<'info> {
@@ -32,9 +43,19 @@ impl<'info> Initialize<'info> {
         ctx: &mut Context<'_, '_, 'info, 'info, Self>,
         params: InitializeParams,
     ) -> Result<()> {
+        if params.commission_basis_points as u64 > BASIC_POINTS {
+            return Err(ErrorCode::InvalidCommission.into());
+        }
+        if params.allowed_mints.len() > MAX_ALLOWED_MINTS {
+            return Err(ErrorCode::TooManyAllowedMints.into());
+        }
+
         ctx.accounts.settings.cancel_timeout = params.cancel_timeout;
         ctx.accounts.settings.commission_basis_points = params.commission_basis_points;
         ctx.accounts.settings.owner = params.owner;
+        ctx.accounts.settings.version = Settings::VERSION;
+        ctx.accounts.settings.tenant_id = params.tenant_id;
+        ctx.accounts.settings.allowed_mints = params.allowed_mints;
         Ok(())
     }
 }
@@ -4,26 +4,26 @@ use anchor_lang::{
 };
 use anchor_spl::token::{initialize_account, InitializeAccount, Mint, Token, TokenAccount};
 
-use std::mem::size_of;
-
 use crate::{
-    error::ErrorCode, Agreement, Settings, AGREEMENT_SEED, PAYMENT_SEED, PROJECT_SEED,
-    SETTINGS_SEED,
+    error::ErrorCode, AdminAction, Agreement, AgreementInitialized, FeeRecipient, FeeShare,
+    Proposal, RevenueShare, Role, RoleKind, Settings, AGREEMENT_SEED, BASIC_POINTS,
+    MAX_DOCUMENT_URI_LEN, MAX_REVENUE_SHARES, PAYMENT_SEED, PRICE_SCALE, PROJECT_SEED,
+    PROPOSAL_SEED, ROLE_SEED, SETTINGS_SEED,
 };
 
 #[derive(Accounts)]
 #[instruction(params: InitializeAgreementParams)]
 pub struct InitializeAgreement<'info> {
-    #[account(
-        seeds = [SETTINGS_SEED.as_bytes()],
+    #[account(mut,
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
         bump
     )]
     pub settings: Account<'info, Settings>,
     #[account(
         init,
         payer = payer,
-        space = 8 + size_of::<Agreement>(),
-        seeds = [AGREEMENT_SEED.as_bytes(), params.agreement_id.to_le_bytes().as_ref()],
+        space = Agreement::SPACE,
+        seeds = [AGREEMENT_SEED.as_bytes(), settings.tenant_seed().as_ref(), params.agreement_id.unwrap_or(settings.next_agreement_id).to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
@@ -31,13 +31,13 @@ pub struct InitializeAgreement<'info> {
     pub project_token_mint: Account<'info, Mint>,
     /// CHECK: Valid PDA, will be initialized.
     #[account(mut,
-        seeds = [PAYMENT_SEED.as_bytes(), params.agreement_id.to_le_bytes().as_ref()],
+        seeds = [PAYMENT_SEED.as_bytes(), settings.tenant_seed().as_ref(), params.agreement_id.unwrap_or(settings.next_agreement_id).to_le_bytes().as_ref()],
         bump,
     )]
     pub payment_token_account: UncheckedAccount<'info>,
     /// CHECK: Valid PDA, will be initialized.
     #[account(mut,
-        seeds = [PROJECT_SEED.as_bytes(), params.agreement_id.to_le_bytes().as_ref()],
+        seeds = [PROJECT_SEED.as_bytes(), settings.tenant_seed().as_ref(), params.agreement_id.unwrap_or(settings.next_agreement_id).to_le_bytes().as_ref()],
         bump,
     )]
     pub project_token_account: UncheckedAccount<'info>,
@@ -45,9 +45,17 @@ pub struct InitializeAgreement<'info> {
     pub company_wallet: UncheckedAccount<'info>,
     /// CHECK: can be arbitrary account
     pub token_seller: AccountInfo<'info>,
+    #[account(
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), RoleKind::Operator.seed().as_ref(), payer.key().as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Option<Account<'info, Role>>,
     #[account(mut,
-        address = settings.owner @ ErrorCode::NotAuthorized,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
     )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -56,17 +64,91 @@ pub struct InitializeAgreement<'info> {
 
 #[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
 pub struct InitializeAgreementParams {
-    pub agreement_id: u64,
+    /// Explicit id below `Settings::next_agreement_id` for agreements allocated off-chain before the
+    /// counter, `None` takes the next id from settings.
+    pub agreement_id: Option<u64>,
     pub expected_payment: u64,
     pub expected_tokens: u64,
     pub hold_duration: i64,
+    /// Hash of the legal terms the seller and company countersign in `accept_agreement`.
+    pub document_hash: [u8; 32],
+    pub document_uri: String,
+    pub company_acceptance_required: bool,
+    pub compliance_required: bool,
+    /// Seconds each investor has to deposit before the allocation passes to the waitlist, 0 disables.
+    pub deposit_window: i64,
+    /// Minimum raise by `funding_deadline` with `expected_payment` as the hard cap, 0 requires the full amount.
+    pub soft_cap: u64,
+    pub funding_deadline: i64,
+    /// Fixed price sale open to any wallet, `None` keeps the round to allocated investors.
+    pub public_sale: Option<PublicSaleTerms>,
+    /// End of the commit window for oversubscribed rounds settled pro rata, 0 disables.
+    pub commit_deadline: i64,
+    /// Bond the seller posts in the payment mint on acceptance, `None` for no bond.
+    pub seller_bond: Option<SellerBondTerms>,
+    /// Recipients sharing the seller proceeds, `None` pays everything to the seller.
+    pub revenue_split: Option<Vec<RevenueShareTerms>>,
+    /// Commission recipients overriding `Settings::fee_split`, `None` uses the platform table.
+    pub fee_split: Option<Vec<FeeShare>>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct PublicSaleTerms {
+    /// Payment units per `PRICE_SCALE` project token units.
+    pub token_price: u64,
+    pub min_ticket: u64,
+    /// 0 for no upper bound.
+    pub max_ticket: u64,
+    /// Total payment per wallet across purchases, 0 for no cap.
+    pub wallet_cap: u64,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct SellerBondTerms {
+    pub amount: u64,
+    /// Tokens must be delivered by this time or the bond can be slashed.
+    pub delivery_deadline: i64,
+    /// Slashed bond goes pro rata to funded investors, otherwise to the company wallet.
+    pub to_investors: bool,
+    /// Seconds investors have to claim a bond slashed to them, the rest then goes to the company
+    /// wallet.
+    pub claim_window: i64,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct RevenueShareTerms {
+    pub recipient: Pubkey,
+    /// Share of the seller proceeds, all recipients add up to `BASIC_POINTS`.
+    pub basis_points: u16,
 }
 
 impl<'info> InitializeAgreement<'info> {
     pub fn handle(
         ctx: &mut Context<'_, '_, 'info, 'info, Self>,
         params: InitializeAgreementParams,
-    ) -> Result<()> {
+    ) -> Result<u64> {
+        if ctx.accounts.settings.multisig_enabled() {
+            let action = AdminAction::InitializeAgreement {
+                params: Box::new(params.clone()),
+                payment_token_mint: ctx.accounts.payment_token_mint.key(),
+                project_token_mint: ctx.accounts.project_token_mint.key(),
+                company_wallet: ctx.accounts.company_wallet.key(),
+                token_seller: ctx.accounts.token_seller.key(),
+            };
+            ctx.accounts
+                .proposal
+                .as_mut()
+                .ok_or(ErrorCode::ProposalRequired)?
+                .execute(
+                    &ctx.accounts.settings,
+                    &action,
+                    Clock::get()?.unix_timestamp,
+                )?;
+        } else if ctx.accounts.operator.is_none()
+            && ctx.accounts.settings.owner != *ctx.accounts.payer.key
+        {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
         if params.expected_payment == 0 {
             return Err(ErrorCode::InvalidAmount.into());
         }
@@ -76,15 +158,100 @@ impl<'info> InitializeAgreement<'info> {
         if params.hold_duration <= 0 {
             return Err(ErrorCode::InvalidHoldDuration.into());
         }
+        if params.soft_cap > params.expected_payment
+            || (params.soft_cap > 0 && params.funding_deadline <= Clock::get()?.unix_timestamp)
+        {
+            return Err(ErrorCode::InvalidSoftCap.into());
+        }
+        if params.deposit_window < 0 {
+            return Err(ErrorCode::InvalidDepositWindow.into());
+        }
+        if let Some(terms) = &params.public_sale {
+            // Selling every token at the price must raise exactly the expected payment
+            if terms.token_price == 0
+                || terms.token_price as u128 * params.expected_tokens as u128
+                    != params.expected_payment as u128 * PRICE_SCALE as u128
+                || (terms.max_ticket > 0 && terms.max_ticket < terms.min_ticket)
+                || (terms.wallet_cap > 0 && terms.wallet_cap < terms.min_ticket)
+            {
+                return Err(ErrorCode::InvalidPublicSaleTerms.into());
+            }
+        }
+        // Pro rata rounds size every allocation at settlement, so other round modes don't apply
+        if params.commit_deadline != 0
+            && (params.commit_deadline <= Clock::get()?.unix_timestamp
+                || params.soft_cap > 0
+                || params.deposit_window > 0
+                || params.public_sale.is_some())
+        {
+            return Err(ErrorCode::InvalidCommitWindow.into());
+        }
+        if let Some(terms) = &params.seller_bond {
+            if terms.amount == 0
+                || terms.delivery_deadline <= Clock::get()?.unix_timestamp
+                || (terms.to_investors && terms.claim_window <= 0)
+            {
+                return Err(ErrorCode::InvalidSellerBond.into());
+            }
+        }
+        if let Some(split) = &params.revenue_split {
+            if split.is_empty()
+                || split.len() > MAX_REVENUE_SHARES
+                || split
+                    .iter()
+                    .any(|share| share.basis_points == 0 || share.recipient == Pubkey::default())
+                || split
+                    .iter()
+                    .map(|share| share.basis_points as u64)
+                    .sum::<u64>()
+                    != BASIC_POINTS
+            {
+                return Err(ErrorCode::InvalidRevenueSplit.into());
+            }
+        }
+        if let Some(fee_split) = &params.fee_split {
+            if !FeeShare::valid_split(fee_split) {
+                return Err(ErrorCode::InvalidFeeSplit.into());
+            }
+        }
+        if params.document_uri.len() > MAX_DOCUMENT_URI_LEN {
+            return Err(ErrorCode::DocumentUriTooLong.into());
+        }
+        if !ctx.accounts.settings.allowed_mints.is_empty()
+            && !ctx
+                .accounts
+                .settings
+                .allowed_mints
+                .contains(ctx.accounts.payment_token_mint.to_account_info().key)
+        {
+            return Err(ErrorCode::PaymentMintNotAllowed.into());
+        }
+
+        let agreement_id = match params.agreement_id {
+            // Ids from the counter are never taken explicitly, so it can't collide later
+            Some(agreement_id) if agreement_id >= ctx.accounts.settings.next_agreement_id => {
+                return Err(ErrorCode::AgreementIdNotReserved.into());
+            }
+            Some(agreement_id) => agreement_id,
+            None => {
+                let agreement_id = ctx.accounts.settings.next_agreement_id;
+                ctx.accounts.settings.next_agreement_id = agreement_id
+                    .checked_add(1)
+                    .ok_or(ErrorCode::AgreementIdOverflow)?;
+                agreement_id
+            }
+        };
 
         // Create token accounts for payment and project tokens
+        let tenant_seed = ctx.accounts.settings.tenant_seed();
         for (mint, token, seeds) in &[
             (
                 &ctx.accounts.payment_token_mint,
                 &ctx.accounts.payment_token_account,
                 [
                     PAYMENT_SEED.as_bytes(),
-                    params.agreement_id.to_le_bytes().as_ref(),
+                    tenant_seed.as_ref(),
+                    agreement_id.to_le_bytes().as_ref(),
                     &[ctx.bumps.payment_token_account],
                 ],
             ),
@@ -93,7 +260,8 @@ impl<'info> InitializeAgreement<'info> {
                 &ctx.accounts.project_token_account,
                 [
                     PROJECT_SEED.as_bytes(),
-                    params.agreement_id.to_le_bytes().as_ref(),
+                    tenant_seed.as_ref(),
+                    agreement_id.to_le_bytes().as_ref(),
                     &[ctx.bumps.project_token_account],
                 ],
             ),
@@ -124,8 +292,13 @@ impl<'info> InitializeAgreement<'info> {
             ))?;
         }
 
+        // The fee split is fixed at creation, later platform changes apply to new agreements
+        let fee_split = params
+            .fee_split
+            .unwrap_or_else(|| ctx.accounts.settings.fee_split.clone());
+
         let agreement = &mut ctx.accounts.agreement;
-        agreement.agreement_id = params.agreement_id;
+        agreement.agreement_id = agreement_id;
         agreement.payment_token_mint = *ctx.accounts.payment_token_mint.to_account_info().key;
         agreement.project_token_mint = *ctx.accounts.project_token_mint.to_account_info().key;
         agreement.company_wallet = ctx.accounts.company_wallet.key();
@@ -135,7 +308,56 @@ impl<'info> InitializeAgreement<'info> {
         agreement.hold_duration = params.hold_duration;
         agreement.bump = ctx.bumps.agreement;
         agreement.owner = *ctx.accounts.payer.key;
+        agreement.version = Agreement::VERSION;
+        agreement.tenant_id = ctx.accounts.settings.tenant_id;
+        agreement.document_hash = params.document_hash;
+        agreement.document_uri = params.document_uri;
+        agreement.acceptance_required = true;
+        agreement.company_acceptance_required = params.company_acceptance_required;
+        agreement.compliance_required = params.compliance_required;
+        agreement.deposit_window = params.deposit_window;
+        agreement.soft_cap = params.soft_cap;
+        agreement.funding_deadline = params.funding_deadline;
+        agreement.commit_deadline = params.commit_deadline;
+        if let Some(terms) = params.public_sale {
+            agreement.token_price = terms.token_price;
+            agreement.min_ticket = terms.min_ticket;
+            agreement.max_ticket = terms.max_ticket;
+            agreement.wallet_cap = terms.wallet_cap;
+        }
+        if let Some(terms) = params.seller_bond {
+            agreement.seller_bond = terms.amount;
+            agreement.delivery_deadline = terms.delivery_deadline;
+            agreement.bond_to_investors = terms.to_investors;
+            agreement.bond_claim_window = terms.claim_window;
+        }
+        if let Some(split) = params.revenue_split {
+            agreement.revenue_split = split
+                .iter()
+                .map(|terms| RevenueShare {
+                    recipient: terms.recipient,
+                    basis_points: terms.basis_points,
+                    paid: 0,
+                    escrowed: 0,
+                })
+                .collect();
+        }
+
+        agreement.fee_split = fee_split
+            .iter()
+            .map(|share| FeeRecipient {
+                recipient: share.recipient,
+                basis_points: share.basis_points,
+                funds_collected: 0,
+                tokens_collected: 0,
+            })
+            .collect();
+
+        emit!(AgreementInitialized {
+            agreement: agreement.key(),
+            agreement_id,
+        });
 
-        Ok(())
+        Ok(agreement_id)
     }
 }
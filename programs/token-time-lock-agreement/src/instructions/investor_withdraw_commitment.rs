@@ -0,0 +1,211 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{spl_token::native_mint, Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    error::ErrorCode, utils::unwrap_native, Agreement, BlockedWallet, CommitmentWithdrawn,
+    Investor, PayoutEscrowed, Referrer, AGREEMENT_SEED, BLOCKLIST_SEED, INVESTOR_SEED,
+    PAYMENT_SEED, REFERRER_SEED, UNWRAP_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: InvestorWithdrawCommitmentParams)]
+pub struct InvestorWithdrawCommitment<'info> {
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    /// Closed to `rent_payer`, unless the refund is escrowed for a blocked investor.
+    #[account(mut,
+        seeds = [INVESTOR_SEED.as_bytes(), agreement.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub investor: Account<'info, Investor>,
+    /// CHECK: Blocklist PDA of the investor, blocked refunds stay escrowed in the vault.
+    #[account(
+        seeds = [BLOCKLIST_SEED.as_bytes(), agreement.tenant_seed().as_ref(), investor.wallet.as_ref()],
+        bump,
+    )]
+    pub blocked_wallet: UncheckedAccount<'info>,
+    /// Required when the investor was referred, its referral count is released.
+    #[account(mut,
+        seeds = [REFERRER_SEED.as_bytes(), agreement.tenant_seed().as_ref(), investor.referrer.as_ref()],
+        bump = referrer.bump,
+    )]
+    pub referrer: Option<Account<'info, Referrer>>,
+    #[account(mut,
+        seeds = [PAYMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref(), investor.payment_mint_seed().as_ref()],
+        bump,
+    )]
+    pub payment_token_account: Account<'info, TokenAccount>,
+    /// `None` refunds the investor in lamports from a native mint vault.
+    #[account(mut)]
+    pub investor_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Temporary token account created and closed to unwrap the refund.
+    #[account(mut,
+        seeds = [UNWRAP_SEED.as_bytes(), agreement.key().as_ref(), investor.wallet.as_ref()],
+        bump,
+    )]
+    pub unwrap_token_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID @ ErrorCode::InvalidPaymentMint)]
+    pub native_mint: Option<Account<'info, Mint>>,
+    /// CHECK: Investor wallet receiving the unwrapped refund.
+    #[account(mut,
+        address = investor.wallet @ ErrorCode::InvalidDestination,
+    )]
+    pub destination: Option<UncheckedAccount<'info>>,
+    /// CHECK: Paid the investor account rent, the owner or operator in `add_investors` or the
+    /// investor in `commit_funds` and `purchase_tokens`, receives it back.
+    #[account(mut,
+        address = investor.rent_recipient(agreement.owner) @ ErrorCode::InvalidDestination,
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct InvestorWithdrawCommitmentParams;
+
+impl<'info> InvestorWithdrawCommitment<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: InvestorWithdrawCommitmentParams,
+    ) -> Result<()> {
+        if ctx.accounts.agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if ctx.accounts.agreement.hold_duration_start != 0 {
+            return Err(ErrorCode::FundingCompleted.into());
+        }
+        // Settlement splits the round by the commitments at the close of the window
+        if ctx.accounts.agreement.commit_deadline != 0
+            && Clock::get()?.unix_timestamp > ctx.accounts.agreement.commit_deadline
+        {
+            return Err(ErrorCode::CommitWindowClosed.into());
+        }
+        if ctx.accounts.investor.has_withdrawn_tokens || ctx.accounts.investor.escrowed_amount > 0 {
+            return Err(ErrorCode::TokensAlreadyWithdrawn.into());
+        }
+        if let Some(investor_token_account) = &ctx.accounts.investor_token_account {
+            if investor_token_account.owner != ctx.accounts.investor.wallet
+                || investor_token_account.mint != ctx.accounts.payment_token_account.mint
+            {
+                return Err(ErrorCode::InvalidDestination.into());
+            }
+        }
+
+        let (refund_amount, invested_amount, tokens_sold) =
+            if ctx.accounts.investor.investor_deposited {
+                (
+                    ctx.accounts.investor.refund_amount(),
+                    ctx.accounts.investor.required_amount,
+                    ctx.accounts.investor.token_allocation,
+                )
+            } else {
+                (0, 0, 0)
+            };
+        let blocked = BlockedWallet::exists(&ctx.accounts.blocked_wallet);
+        if refund_amount > 0 && blocked {
+            ctx.accounts.investor.escrowed_amount = refund_amount;
+            ctx.accounts.investor.investor_deposited = false;
+            ctx.accounts.investor.refunded = true;
+            emit!(PayoutEscrowed {
+                agreement: ctx.accounts.agreement.key(),
+                wallet: ctx.accounts.investor.wallet,
+                amount: refund_amount,
+            });
+        } else if refund_amount > 0 {
+            match &ctx.accounts.investor_token_account {
+                Some(investor_token_account) => anchor_spl::token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.payment_token_account.to_account_info(),
+                            to: investor_token_account.to_account_info(),
+                            authority: ctx.accounts.agreement.to_account_info(),
+                        },
+                        &[&[
+                            AGREEMENT_SEED.as_bytes(),
+                            ctx.accounts.agreement.tenant_seed().as_ref(),
+                            ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
+                            &[ctx.accounts.agreement.bump],
+                        ]],
+                    ),
+                    refund_amount,
+                )?,
+                None => unwrap_native(
+                    &ctx.accounts.agreement,
+                    &ctx.accounts.payment_token_account,
+                    &ctx.accounts
+                        .native_mint
+                        .as_ref()
+                        .ok_or(ErrorCode::InvalidPaymentMint)?
+                        .to_account_info(),
+                    &ctx.accounts
+                        .unwrap_token_account
+                        .as_ref()
+                        .ok_or(ErrorCode::InvalidDestination)?
+                        .to_account_info(),
+                    ctx.bumps.unwrap_token_account.unwrap_or_default(),
+                    &ctx.accounts
+                        .destination
+                        .as_ref()
+                        .ok_or(ErrorCode::InvalidDestination)?
+                        .to_account_info(),
+                    &ctx.accounts.payer.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    &ctx.accounts.token_program.to_account_info(),
+                    refund_amount,
+                )?,
+            }
+        }
+
+        // Free the allocation so the owner can reassign it with `add_investors`
+        let agreement = &mut ctx.accounts.agreement;
+        agreement.total_invested -= invested_amount;
+        if ctx.accounts.investor.payment_mint != Pubkey::default() {
+            agreement.secondary_invested -= invested_amount;
+        }
+        agreement.tokens_sold -= tokens_sold;
+        agreement.total_required -= ctx.accounts.investor.required_amount;
+        agreement.total_token_allocation -= ctx.accounts.investor.token_allocation;
+        agreement.investors_count -= 1;
+        if agreement.commit_deadline != 0 {
+            agreement.total_committed -= invested_amount;
+        }
+        if ctx.accounts.investor.referrer != Pubkey::default() {
+            let referrer = ctx
+                .accounts
+                .referrer
+                .as_mut()
+                .ok_or(ErrorCode::InvalidReferrerAccount)?;
+            referrer.referred_count = referrer
+                .referred_count
+                .checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(CommitmentWithdrawn {
+            agreement: agreement.key(),
+            wallet: ctx.accounts.investor.wallet,
+            amount: refund_amount,
+        });
+
+        // An escrowed refund is released from the investor account by `release_escrow`, which
+        // keeps no allocation to deposit against
+        if refund_amount > 0 && blocked {
+            ctx.accounts.investor.required_amount = 0;
+            ctx.accounts.investor.token_allocation = 0;
+            ctx.accounts.investor.referrer = Pubkey::default();
+        } else {
+            ctx.accounts
+                .investor
+                .close(ctx.accounts.rent_payer.to_account_info())?;
+        }
+
+        Ok(())
+    }
+}
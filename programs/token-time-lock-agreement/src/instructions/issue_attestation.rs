@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, Attestation, AttestationIssued, Role, RoleKind, Settings, ATTESTATION_SEED,
+    ROLE_SEED, SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: IssueAttestationParams)]
+pub struct IssueAttestation<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), RoleKind::Compliance.seed().as_ref(), payer.key().as_ref()],
+        bump = compliance.bump,
+    )]
+    pub compliance: Account<'info, Role>,
+    #[account(
+        init,
+        payer = payer,
+        space = Attestation::SPACE,
+        seeds = [ATTESTATION_SEED.as_bytes(), settings.tenant_seed().as_ref(), params.wallet.as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct IssueAttestationParams {
+    pub wallet: Pubkey,
+    pub jurisdiction: u16,
+    pub expires_at: i64,
+}
+
+impl<'info> IssueAttestation<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: IssueAttestationParams,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if params.expires_at <= now {
+            return Err(ErrorCode::AttestationExpired.into());
+        }
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.tenant_id = ctx.accounts.settings.tenant_id;
+        attestation.wallet = params.wallet;
+        attestation.issuer = *ctx.accounts.payer.key;
+        attestation.jurisdiction = params.jurisdiction;
+        attestation.issued_at = now;
+        attestation.expires_at = params.expires_at;
+        attestation.bump = ctx.bumps.attestation;
+        attestation.version = Attestation::VERSION;
+
+        emit!(AttestationIssued {
+            attestation: attestation.key(),
+            wallet: attestation.wallet,
+            jurisdiction: attestation.jurisdiction,
+            expires_at: attestation.expires_at,
+        });
+
+        Ok(())
+    }
+}
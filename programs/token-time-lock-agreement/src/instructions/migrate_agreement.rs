@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, utils::realloc_account, Agreement, Settings, AGREEMENT_SEED, SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: MigrateAgreementParams)]
+pub struct MigrateAgreement<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    /// CHECK: Resized and deserialized in the handler, old layouts cannot be loaded as `Agreement`.
+    #[account(mut)]
+    pub agreement: UncheckedAccount<'info>,
+    #[account(mut,
+        address = settings.owner @ ErrorCode::NotAuthorized,
+    )]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateAgreementParams;
+
+impl<'info> MigrateAgreement<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: MigrateAgreementParams,
+    ) -> Result<()> {
+        let agreement_info = ctx.accounts.agreement.to_account_info();
+        if *agreement_info.owner != crate::ID
+            || !agreement_info
+                .try_borrow_data()?
+                .starts_with(Agreement::DISCRIMINATOR)
+        {
+            return Err(ErrorCode::InvalidAccountLayout.into());
+        }
+
+        if agreement_info.try_borrow_data()?[Agreement::LEGACY_LEN] >= Agreement::VERSION {
+            return Err(ErrorCode::AlreadyMigrated.into());
+        }
+
+        realloc_account(
+            &agreement_info,
+            Agreement::SPACE,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        agreement_info.try_borrow_mut_data()?[Agreement::LEGACY_LEN..].fill(0);
+
+        let mut agreement =
+            Agreement::try_deserialize(&mut &agreement_info.try_borrow_data()?[..])?;
+        if agreement.tenant_id != ctx.accounts.settings.tenant_id {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+        let address = Pubkey::create_program_address(
+            &[
+                AGREEMENT_SEED.as_bytes(),
+                agreement.tenant_seed().as_ref(),
+                agreement.agreement_id.to_le_bytes().as_ref(),
+                &[agreement.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::InvalidAccountLayout)?;
+        if address != agreement_info.key() {
+            return Err(ErrorCode::InvalidAccountLayout.into());
+        }
+
+        agreement.version = Agreement::VERSION;
+        agreement.try_serialize(&mut &mut agreement_info.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, utils::realloc_account, Agreement, Investor, Settings, AGREEMENT_SEED,
+    INVESTOR_SEED, SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: MigrateInvestorParams)]
+pub struct MigrateInvestor<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    /// CHECK: Resized and deserialized in the handler, old layouts cannot be loaded as `Investor`.
+    #[account(mut)]
+    pub investor: UncheckedAccount<'info>,
+    #[account(mut,
+        address = settings.owner @ ErrorCode::NotAuthorized,
+    )]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateInvestorParams;
+
+impl<'info> MigrateInvestor<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: MigrateInvestorParams,
+    ) -> Result<()> {
+        let investor_info = ctx.accounts.investor.to_account_info();
+        if *investor_info.owner != crate::ID
+            || !investor_info
+                .try_borrow_data()?
+                .starts_with(Investor::DISCRIMINATOR)
+        {
+            return Err(ErrorCode::InvalidAccountLayout.into());
+        }
+
+        if investor_info.try_borrow_data()?[Investor::LEGACY_LEN] >= Investor::VERSION {
+            return Err(ErrorCode::AlreadyMigrated.into());
+        }
+
+        realloc_account(
+            &investor_info,
+            Investor::SPACE,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        investor_info.try_borrow_mut_data()?[Investor::LEGACY_LEN..].fill(0);
+
+        let mut investor = Investor::try_deserialize(&mut &investor_info.try_borrow_data()?[..])?;
+        let address = Pubkey::create_program_address(
+            &[
+                INVESTOR_SEED.as_bytes(),
+                investor.agreement.as_ref(),
+                investor.wallet.as_ref(),
+                &[investor.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::InvalidInvestorAccount)?;
+        if investor.agreement != ctx.accounts.agreement.key() || address != investor_info.key() {
+            return Err(ErrorCode::InvalidInvestorAccount.into());
+        }
+
+        investor.version = Investor::VERSION;
+        investor.try_serialize(&mut &mut investor_info.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, tenant_seed, utils::realloc_account, Settings, SETTINGS_SEED};
+
+#[derive(Accounts)]
+#[instruction(params: MigrateSettingsParams)]
+pub struct MigrateSettings<'info> {
+    /// CHECK: Resized and deserialized in the handler, old layouts cannot be loaded as `Settings`.
+    #[account(mut,
+        seeds = [SETTINGS_SEED.as_bytes(), tenant_seed(params.tenant_id).as_ref()],
+        bump,
+    )]
+    pub settings: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateSettingsParams {
+    pub tenant_id: u64,
+    /// Past the highest id handed out before the counter, ids below it stay available explicitly.
+    pub next_agreement_id: u64,
+}
+
+impl<'info> MigrateSettings<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: MigrateSettingsParams,
+    ) -> Result<()> {
+        let settings_info = ctx.accounts.settings.to_account_info();
+        if *settings_info.owner != crate::ID
+            || !settings_info
+                .try_borrow_data()?
+                .starts_with(Settings::DISCRIMINATOR)
+        {
+            return Err(ErrorCode::InvalidAccountLayout.into());
+        }
+        if settings_info.try_borrow_data()?[Settings::LEGACY_LEN] >= Settings::VERSION {
+            return Err(ErrorCode::AlreadyMigrated.into());
+        }
+
+        realloc_account(
+            &settings_info,
+            Settings::SPACE,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        // Everything past the unversioned fields is zeroed before the new layout is read, so
+        // leftover bytes can't surface as new fields
+        settings_info.try_borrow_mut_data()?[Settings::LEGACY_LEN..].fill(0);
+
+        let mut settings = Settings::try_deserialize(&mut &settings_info.try_borrow_data()?[..])?;
+        if settings.tenant_id != params.tenant_id || settings.owner != *ctx.accounts.payer.key {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+
+        settings.version = Settings::VERSION;
+        settings.next_agreement_id = params.next_agreement_id;
+        settings.try_serialize(&mut &mut settings_info.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+}
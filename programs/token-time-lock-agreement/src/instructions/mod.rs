@@ -1,25 +1,91 @@
+pub mod accept_agreement;
+pub mod acknowledge_document;
 pub mod add_investors;
+pub mod add_payment_mint;
+pub mod add_to_waitlist;
+pub mod approve_milestone;
+pub mod approve_proposal;
+pub mod block_wallet;
 pub mod cancel_agreement;
+pub mod claim_bond_share;
+pub mod commit_funds;
+pub mod create_proposal;
+pub mod deposit_project_tokens;
 pub mod deposit_stablecoins;
+pub mod expire_allocation;
+pub mod finalize_funding;
+pub mod grant_role;
 pub mod initialize;
 pub mod initialize_agreement;
+pub mod investor_withdraw_commitment;
+pub mod issue_attestation;
+pub mod migrate_agreement;
+pub mod migrate_investor;
+pub mod migrate_settings;
 pub mod process_token_deposit;
+pub mod purchase_tokens;
+pub mod reclaim_seller_bond;
+pub mod release_escrow;
+pub mod release_revenue_share;
+pub mod revoke_attestation;
+pub mod revoke_role;
+pub mod set_milestones;
+pub mod settle_commitments;
+pub mod skip_waitlist_entry;
+pub mod slash_seller_bond;
+pub mod sweep_bond_shares;
+pub mod unblock_wallet;
+pub mod update_settings;
 pub mod withdraw_cancelled_funds;
 pub mod withdraw_cancelled_funds_batch;
 pub mod withdraw_excess_tokens;
 pub mod withdraw_funds;
 pub mod withdraw_tokens;
 pub mod withdraw_tokens_batch;
+pub mod withdraw_unsold_tokens;
 
+pub use accept_agreement::*;
+pub use acknowledge_document::*;
 pub use add_investors::*;
+pub use add_payment_mint::*;
+pub use add_to_waitlist::*;
+pub use approve_milestone::*;
+pub use approve_proposal::*;
+pub use block_wallet::*;
 pub use cancel_agreement::*;
+pub use claim_bond_share::*;
+pub use commit_funds::*;
+pub use create_proposal::*;
+pub use deposit_project_tokens::*;
 pub use deposit_stablecoins::*;
+pub use expire_allocation::*;
+pub use finalize_funding::*;
+pub use grant_role::*;
 pub use initialize::*;
 pub use initialize_agreement::*;
+pub use investor_withdraw_commitment::*;
+pub use issue_attestation::*;
+pub use migrate_agreement::*;
+pub use migrate_investor::*;
+pub use migrate_settings::*;
 pub use process_token_deposit::*;
+pub use purchase_tokens::*;
+pub use reclaim_seller_bond::*;
+pub use release_escrow::*;
+pub use release_revenue_share::*;
+pub use revoke_attestation::*;
+pub use revoke_role::*;
+pub use set_milestones::*;
+pub use settle_commitments::*;
+pub use skip_waitlist_entry::*;
+pub use slash_seller_bond::*;
+pub use sweep_bond_shares::*;
+pub use unblock_wallet::*;
+pub use update_settings::*;
 pub use withdraw_cancelled_funds::*;
 pub use withdraw_cancelled_funds_batch::*;
 pub use withdraw_excess_tokens::*;
 pub use withdraw_funds::*;
 pub use withdraw_tokens::*;
 pub use withdraw_tokens_batch::*;
+pub use withdraw_unsold_tokens::*;
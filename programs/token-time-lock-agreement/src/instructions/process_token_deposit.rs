@@ -1,23 +1,33 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount, Transfer};
 
-use crate::{error::ErrorCode, Agreement, AGREEMENT_SEED, PROJECT_SEED};
+use crate::{
+    error::ErrorCode, utils::return_seller_bond, Agreement, AGREEMENT_SEED, BOND_SEED, PROJECT_SEED,
+};
 
 #[derive(Accounts)]
 #[instruction(params: ProcessTokenDepositParams)]
 pub struct ProcessTokenDeposit<'info> {
     #[account(mut,
-        seeds = [AGREEMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
     #[account(mut,
-        seeds = [PROJECT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [PROJECT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump,
     )]
     pub project_token_account: Account<'info, TokenAccount>,
     #[account(mut)]
     pub company_token_account: Account<'info, TokenAccount>,
+    #[account(mut,
+        seeds = [BOND_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub bond_token_account: Option<Account<'info, TokenAccount>>,
+    /// Receives the seller bond back when tokens arrive before `delivery_deadline`.
+    #[account(mut)]
+    pub seller_payment_token_account: Option<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -39,6 +49,9 @@ impl<'info> ProcessTokenDeposit<'info> {
         if ctx.accounts.agreement.seller_deposited {
             return Err(ErrorCode::TokensAlreadyDeposited.into());
         }
+        if !ctx.accounts.agreement.accepted() {
+            return Err(ErrorCode::AgreementNotAccepted.into());
+        }
         if ctx.accounts.company_token_account.mint != ctx.accounts.agreement.project_token_mint
             || ctx.accounts.company_token_account.owner != ctx.accounts.agreement.company_wallet
         {
@@ -63,6 +76,7 @@ impl<'info> ProcessTokenDeposit<'info> {
                     },
                     &[&[
                         AGREEMENT_SEED.as_bytes(),
+                        ctx.accounts.agreement.tenant_seed().as_ref(),
                         ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
                         &[ctx.accounts.agreement.bump],
                     ]],
@@ -74,6 +88,19 @@ impl<'info> ProcessTokenDeposit<'info> {
         ctx.accounts.agreement.seller_deposited = true;
         ctx.accounts.agreement.first_deposit_time = ctx.accounts.clock.unix_timestamp;
 
+        if ctx
+            .accounts
+            .agreement
+            .bond_returnable(ctx.accounts.clock.unix_timestamp)
+        {
+            return_seller_bond(
+                &mut ctx.accounts.agreement,
+                ctx.accounts.bond_token_account.as_ref(),
+                ctx.accounts.seller_payment_token_account.as_ref(),
+                &ctx.accounts.token_program.to_account_info(),
+            )?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,219 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+use crate::{
+    error::ErrorCode,
+    utils::{create_pda_account, wrap_native},
+    Acknowledgement, Agreement, Attestation, BlockedWallet, Investor, TokensPurchased,
+    ACKNOWLEDGEMENT_SEED, AGREEMENT_SEED, ATTESTATION_SEED, BLOCKLIST_SEED, INVESTOR_SEED,
+    PAYMENT_SEED, PRICE_SCALE,
+};
+
+#[derive(Accounts)]
+#[instruction(params: PurchaseTokensParams)]
+pub struct PurchaseTokens<'info> {
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    /// CHECK: Investor PDA of the buyer, created on the first purchase.
+    #[account(mut,
+        seeds = [INVESTOR_SEED.as_bytes(), agreement.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub investor: UncheckedAccount<'info>,
+    #[account(
+        seeds = [ACKNOWLEDGEMENT_SEED.as_bytes(), agreement.key().as_ref(), payer.key().as_ref()],
+        bump = acknowledgement.bump,
+    )]
+    pub acknowledgement: Option<Account<'info, Acknowledgement>>,
+    #[account(
+        seeds = [ATTESTATION_SEED.as_bytes(), agreement.tenant_seed().as_ref(), payer.key().as_ref()],
+        bump = attestation.bump,
+    )]
+    pub attestation: Option<Account<'info, Attestation>>,
+    /// CHECK: Blocklist PDA of the buyer.
+    #[account(
+        seeds = [BLOCKLIST_SEED.as_bytes(), agreement.tenant_seed().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub blocked_wallet: UncheckedAccount<'info>,
+    #[account(mut,
+        seeds = [PAYMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub payment_token_account: Account<'info, TokenAccount>,
+    /// `None` pays in lamports, wrapped into `payment_token_account` of the native mint.
+    #[account(mut)]
+    pub payer_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct PurchaseTokensParams {
+    pub amount: u64,
+}
+
+impl<'info> PurchaseTokens<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: PurchaseTokensParams,
+    ) -> Result<()> {
+        let agreement = &ctx.accounts.agreement;
+        let now = ctx.accounts.clock.unix_timestamp;
+        if !agreement.public_sale() {
+            return Err(ErrorCode::PublicSaleNotEnabled.into());
+        }
+        if agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if !agreement.seller_deposited {
+            return Err(ErrorCode::SellerMustDepositFirst.into());
+        }
+        if !agreement.accepted() {
+            return Err(ErrorCode::AgreementNotAccepted.into());
+        }
+        if agreement.hold_duration_start != 0 {
+            return Err(ErrorCode::FundingCompleted.into());
+        }
+        if agreement.soft_cap > 0 && now > agreement.funding_deadline {
+            return Err(ErrorCode::FundingDeadlinePassed.into());
+        }
+        if agreement.token_seller == *ctx.accounts.payer.key {
+            return Err(ErrorCode::SellerCannotDepositStablecoins.into());
+        }
+        if BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+            return Err(ErrorCode::WalletBlocked.into());
+        }
+        if agreement.compliance_required {
+            ctx.accounts
+                .attestation
+                .as_ref()
+                .ok_or(ErrorCode::AttestationRequired)?
+                .verify(agreement.tenant_id, ctx.accounts.payer.key, now)?;
+        }
+        if agreement.document_hash != [0; 32] {
+            match &ctx.accounts.acknowledgement {
+                Some(acknowledgement)
+                    if acknowledgement.document_hash == agreement.document_hash => {}
+                _ => return Err(ErrorCode::DocumentNotAcknowledged.into()),
+            }
+        }
+
+        if agreement.total_required + params.amount > agreement.expected_payment {
+            return Err(ErrorCode::DepositExceedsExpectedPayment.into());
+        }
+        // The purchase completing the sale may be below the minimum ticket, the seller withdraws
+        // the rounding dust with `withdraw_unsold_tokens`
+        let completes_sale = agreement.total_required + params.amount == agreement.expected_payment;
+        if params.amount == 0 || (params.amount < agreement.min_ticket && !completes_sale) {
+            return Err(ErrorCode::TicketBelowMinimum.into());
+        }
+        if agreement.max_ticket > 0 && params.amount > agreement.max_ticket {
+            return Err(ErrorCode::TicketAboveMaximum.into());
+        }
+        let tokens =
+            (params.amount as u128 * PRICE_SCALE as u128 / agreement.token_price as u128) as u64;
+        if tokens == 0 && !completes_sale {
+            return Err(ErrorCode::InvalidAmount.into());
+        }
+        if agreement.total_token_allocation + tokens > agreement.expected_tokens {
+            return Err(ErrorCode::TotalTokenAllocationsExceedTarget.into());
+        }
+
+        let investor_account = ctx.accounts.investor.to_account_info();
+        let mut investor = if *investor_account.owner == system_program::ID {
+            create_pda_account(
+                &investor_account,
+                Investor::SPACE,
+                ctx.program_id,
+                &[
+                    INVESTOR_SEED.as_bytes(),
+                    agreement.key().as_ref(),
+                    ctx.accounts.payer.key.as_ref(),
+                    &[ctx.bumps.investor],
+                ],
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+            ctx.accounts.agreement.investors_count += 1;
+
+            Investor {
+                agreement: ctx.accounts.agreement.key(),
+                wallet: *ctx.accounts.payer.key,
+                investor_deposited: true,
+                bump: ctx.bumps.investor,
+                version: Investor::VERSION,
+                rent_payer: *ctx.accounts.payer.key,
+                ..Default::default()
+            }
+        } else {
+            if investor_account.owner != ctx.program_id {
+                return Err(ErrorCode::InvalidInvestorAccountOwner.into());
+            }
+            let investor = Investor::try_deserialize(&mut &investor_account.data.borrow()[..])?;
+            // Allocated investors pay through `deposit_stablecoins`
+            if !investor.investor_deposited {
+                return Err(ErrorCode::InvalidInvestorAccount.into());
+            }
+            investor
+        };
+
+        if ctx.accounts.agreement.wallet_cap > 0
+            && investor.required_amount + params.amount > ctx.accounts.agreement.wallet_cap
+        {
+            return Err(ErrorCode::WalletCapExceeded.into());
+        }
+        investor.required_amount += params.amount;
+        investor.token_allocation += tokens;
+        // Deposits made after the slash do not dilute the bond shares of earlier investors
+        if ctx.accounts.agreement.bond_slashed {
+            investor.deposited_after_slash += params.amount;
+        }
+        investor.try_serialize(&mut *investor_account.try_borrow_mut_data()?)?;
+
+        let agreement = &mut ctx.accounts.agreement;
+        agreement.total_required += params.amount;
+        agreement.total_token_allocation += tokens;
+        agreement.total_invested += params.amount;
+        agreement.tokens_sold += tokens;
+        if agreement.total_invested == agreement.expected_payment {
+            agreement.hold_duration_start = now;
+        }
+
+        match &ctx.accounts.payer_token_account {
+            Some(payer_token_account) => anchor_spl::token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: payer_token_account.to_account_info(),
+                        to: ctx.accounts.payment_token_account.to_account_info(),
+                        authority: ctx.accounts.payer.to_account_info(),
+                    },
+                ),
+                params.amount,
+            )?,
+            None => wrap_native(
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.payment_token_account,
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                params.amount,
+            )?,
+        }
+
+        emit!(TokensPurchased {
+            agreement: agreement.key(),
+            wallet: *ctx.accounts.payer.key,
+            amount: params.amount,
+            tokens,
+        });
+
+        Ok(())
+    }
+}
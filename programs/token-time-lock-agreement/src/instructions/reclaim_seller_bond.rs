@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::{error::ErrorCode, utils::return_seller_bond, Agreement, AGREEMENT_SEED, BOND_SEED};
+
+#[derive(Accounts)]
+#[instruction(params: ReclaimSellerBondParams)]
+pub struct ReclaimSellerBond<'info> {
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(mut,
+        seeds = [BOND_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub bond_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub seller_payment_token_account: Account<'info, TokenAccount>,
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ReclaimSellerBondParams;
+
+impl<'info> ReclaimSellerBond<'info> {
+    /// Returns the bond of a seller whose agreement was cancelled before `delivery_deadline`.
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: ReclaimSellerBondParams,
+    ) -> Result<()> {
+        let agreement = &ctx.accounts.agreement;
+        if agreement.token_seller != *ctx.accounts.payer.key {
+            return Err(ErrorCode::OnlySellerAllowed.into());
+        }
+        if !agreement.bond_deposited
+            || agreement.bond_returned
+            || agreement.bond_slashed
+            || !agreement.cancelled_before_delivery_deadline()
+        {
+            return Err(ErrorCode::SellerBondNotReturnable.into());
+        }
+
+        return_seller_bond(
+            &mut ctx.accounts.agreement,
+            Some(&ctx.accounts.bond_token_account),
+            Some(&ctx.accounts.seller_payment_token_account),
+            &ctx.accounts.token_program.to_account_info(),
+        )
+    }
+}
@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{spl_token::native_mint, Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    error::ErrorCode, utils::unwrap_native, AdminAction, Agreement, BlockedWallet, EscrowReleased,
+    Investor, Proposal, Settings, AGREEMENT_SEED, BLOCKLIST_SEED, INVESTOR_SEED, PROPOSAL_SEED,
+    SETTINGS_SEED, UNWRAP_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: ReleaseEscrowParams)]
+pub struct ReleaseEscrow<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    /// Investor whose payout was escrowed, `None` releases the seller's payout.
+    #[account(mut,
+        seeds = [INVESTOR_SEED.as_bytes(), agreement.key().as_ref(), investor.wallet.as_ref()],
+        bump,
+    )]
+    pub investor: Option<Account<'info, Investor>>,
+    /// CHECK: Blocklist PDA of the payee, must no longer exist.
+    #[account(
+        seeds = [BLOCKLIST_SEED.as_bytes(), agreement.tenant_seed().as_ref(), investor.as_ref().map(|investor| investor.wallet).unwrap_or(agreement.token_seller).as_ref()],
+        bump,
+    )]
+    pub blocked_wallet: UncheckedAccount<'info>,
+    #[account(mut,
+        seeds = [agreement.escrow_vault_seed(investor.as_deref()).as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref(), investor.as_ref().filter(|investor| investor.payment_escrowed()).map(|investor| investor.payment_mint_seed()).unwrap_or_default().as_ref()],
+        bump,
+    )]
+    pub agreement_token_account: Account<'info, TokenAccount>,
+    /// `None` pays the payee in lamports from a native mint vault.
+    #[account(mut)]
+    pub destination_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Temporary token account created and closed to unwrap the payout.
+    #[account(mut,
+        seeds = [UNWRAP_SEED.as_bytes(), agreement.key().as_ref(), investor.as_ref().map(|investor| investor.wallet).unwrap_or(agreement.token_seller).as_ref()],
+        bump,
+    )]
+    pub unwrap_token_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID @ ErrorCode::InvalidPaymentMint)]
+    pub native_mint: Option<Account<'info, Mint>>,
+    /// CHECK: Payee wallet receiving the unwrapped payout.
+    #[account(mut,
+        address = investor.as_ref().map(|investor| investor.wallet).unwrap_or(agreement.token_seller) @ ErrorCode::InvalidDestination,
+    )]
+    pub destination: Option<UncheckedAccount<'info>>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ReleaseEscrowParams;
+
+impl<'info> ReleaseEscrow<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: ReleaseEscrowParams,
+    ) -> Result<()> {
+        let (wallet, amount) = match &ctx.accounts.investor {
+            Some(investor) if investor.escrowed_refund > 0 => {
+                (investor.wallet, investor.escrowed_refund)
+            }
+            Some(investor) => (investor.wallet, investor.escrowed_amount),
+            None if ctx.accounts.agreement.seller_escrowed_amount > 0 => (
+                ctx.accounts.agreement.token_seller,
+                ctx.accounts.agreement.seller_escrowed_amount,
+            ),
+            None => (
+                ctx.accounts.agreement.token_seller,
+                ctx.accounts.agreement.seller_escrowed_tokens,
+            ),
+        };
+        AdminAction::ReleaseEscrow {
+            agreement: ctx.accounts.agreement.key(),
+            wallet,
+        }
+        .authorize(
+            &ctx.accounts.settings,
+            ctx.accounts.proposal.as_mut(),
+            ctx.accounts.payer.key,
+            Clock::get()?.unix_timestamp,
+        )?;
+        if BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+            return Err(ErrorCode::WalletBlocked.into());
+        }
+        if amount == 0 {
+            return Err(ErrorCode::NoFundsToWithdraw.into());
+        }
+        match &ctx.accounts.destination_token_account {
+            Some(destination_token_account) if destination_token_account.owner != wallet => {
+                return Err(ErrorCode::InvalidDestination.into());
+            }
+            Some(destination_token_account) => anchor_spl::token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.agreement_token_account.to_account_info(),
+                        to: destination_token_account.to_account_info(),
+                        authority: ctx.accounts.agreement.to_account_info(),
+                    },
+                    &[&[
+                        AGREEMENT_SEED.as_bytes(),
+                        ctx.accounts.agreement.tenant_seed().as_ref(),
+                        ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
+                        &[ctx.accounts.agreement.bump],
+                    ]],
+                ),
+                amount,
+            )?,
+            None => unwrap_native(
+                &ctx.accounts.agreement,
+                &ctx.accounts.agreement_token_account,
+                &ctx.accounts
+                    .native_mint
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidPaymentMint)?
+                    .to_account_info(),
+                &ctx.accounts
+                    .unwrap_token_account
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidDestination)?
+                    .to_account_info(),
+                ctx.bumps.unwrap_token_account.unwrap_or_default(),
+                &ctx.accounts
+                    .destination
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidDestination)?
+                    .to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                amount,
+            )?,
+        }
+
+        match &mut ctx.accounts.investor {
+            Some(investor) if investor.escrowed_refund > 0 => investor.escrowed_refund = 0,
+            Some(investor) => investor.escrowed_amount = 0,
+            None if ctx.accounts.agreement.seller_escrowed_amount > 0 => {
+                ctx.accounts.agreement.seller_escrowed_amount = 0
+            }
+            None => ctx.accounts.agreement.seller_escrowed_tokens = 0,
+        }
+
+        emit!(EscrowReleased {
+            agreement: ctx.accounts.agreement.key(),
+            wallet,
+            amount,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+use crate::{
+    error::ErrorCode, AdminAction, Agreement, BlockedWallet, EscrowReleased, PaymentMint, Proposal,
+    Settings, AGREEMENT_SEED, PAYMENT_MINT_SEED, PAYMENT_SEED, PROPOSAL_SEED, SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: ReleaseRevenueShareParams)]
+pub struct ReleaseRevenueShare<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    /// Additional mint the share was escrowed in, `None` releases `payment_token_mint`.
+    #[account(mut,
+        seeds = [PAYMENT_MINT_SEED.as_bytes(), agreement.key().as_ref(), payment_mint.mint.as_ref()],
+        bump = payment_mint.bump,
+    )]
+    pub payment_mint: Option<Account<'info, PaymentMint>>,
+    #[account(mut,
+        seeds = [PAYMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref(), payment_mint.as_ref().map(|payment_mint| payment_mint.mint.as_ref()).unwrap_or_default()],
+        bump,
+    )]
+    pub payment_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Blocklist PDA of the recipient, checked in `handle` and must no longer exist.
+    pub blocked_wallet: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ReleaseRevenueShareParams {
+    pub index: u8, // position of the recipient in `Agreement::revenue_split`
+}
+
+impl<'info> ReleaseRevenueShare<'info> {
+    /// Pays a revenue split recipient the share escrowed by `withdraw_funds` while it was blocked.
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: ReleaseRevenueShareParams,
+    ) -> Result<()> {
+        let index = params.index as usize;
+        let recipient = ctx
+            .accounts
+            .agreement
+            .revenue_split
+            .get(index)
+            .ok_or(ErrorCode::InvalidRevenueSplit)?
+            .recipient;
+        AdminAction::ReleaseEscrow {
+            agreement: ctx.accounts.agreement.key(),
+            wallet: recipient,
+        }
+        .authorize(
+            &ctx.accounts.settings,
+            ctx.accounts.proposal.as_mut(),
+            ctx.accounts.payer.key,
+            Clock::get()?.unix_timestamp,
+        )?;
+        if BlockedWallet::is_blocked(
+            &ctx.accounts.blocked_wallet,
+            &ctx.accounts.agreement.tenant_seed(),
+            &recipient,
+        )? {
+            return Err(ErrorCode::WalletBlocked.into());
+        }
+        if ctx.accounts.recipient_token_account.owner != recipient
+            || ctx.accounts.recipient_token_account.mint != ctx.accounts.payment_token_account.mint
+        {
+            return Err(ErrorCode::InvalidDestination.into());
+        }
+
+        let (paid, escrowed) = match &mut ctx.accounts.payment_mint {
+            Some(payment_mint) => {
+                let payout = &mut payment_mint.revenue_split[index];
+                (&mut payout.paid, &mut payout.escrowed)
+            }
+            None => {
+                let share = &mut ctx.accounts.agreement.revenue_split[index];
+                (&mut share.paid, &mut share.escrowed)
+            }
+        };
+        let amount = *escrowed;
+        if amount == 0 {
+            return Err(ErrorCode::NoFundsToWithdraw.into());
+        }
+        *escrowed = 0;
+        *paid += amount;
+
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payment_token_account.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: ctx.accounts.agreement.to_account_info(),
+                },
+                &[&[
+                    AGREEMENT_SEED.as_bytes(),
+                    ctx.accounts.agreement.tenant_seed().as_ref(),
+                    ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
+                    &[ctx.accounts.agreement.bump],
+                ]],
+            ),
+            amount,
+        )?;
+
+        emit!(EscrowReleased {
+            agreement: ctx.accounts.agreement.key(),
+            wallet: recipient,
+            amount,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    Attestation, AttestationRevoked, Role, RoleKind, Settings, ATTESTATION_SEED, ROLE_SEED,
+    SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: RevokeAttestationParams)]
+pub struct RevokeAttestation<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), RoleKind::Compliance.seed().as_ref(), payer.key().as_ref()],
+        bump = compliance.bump,
+    )]
+    pub compliance: Account<'info, Role>,
+    #[account(mut,
+        close = payer,
+        seeds = [ATTESTATION_SEED.as_bytes(), settings.tenant_seed().as_ref(), params.wallet.as_ref()],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct RevokeAttestationParams {
+    pub wallet: Pubkey,
+}
+
+impl<'info> RevokeAttestation<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: RevokeAttestationParams,
+    ) -> Result<()> {
+        emit!(AttestationRevoked {
+            attestation: ctx.accounts.attestation.key(),
+            wallet: ctx.accounts.attestation.wallet,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    AdminAction, Proposal, Role, RoleKind, RoleRevoked, Settings, PROPOSAL_SEED, ROLE_SEED,
+    SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: RevokeRoleParams)]
+pub struct RevokeRole<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        close = payer,
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), params.kind.seed().as_ref(), params.wallet.as_ref()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct RevokeRoleParams {
+    pub wallet: Pubkey,
+    pub kind: RoleKind,
+}
+
+impl<'info> RevokeRole<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: RevokeRoleParams,
+    ) -> Result<()> {
+        AdminAction::RevokeRole {
+            params: params.clone(),
+        }
+        .authorize(
+            &ctx.accounts.settings,
+            ctx.accounts.proposal.as_mut(),
+            ctx.accounts.payer.key,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit!(RoleRevoked {
+            settings: ctx.accounts.role.settings,
+            wallet: ctx.accounts.role.wallet,
+            kind: ctx.accounts.role.kind,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, AdminAction, Agreement, Milestone, MilestonePlan, Proposal, Role, RoleKind,
+    Settings, AGREEMENT_SEED, BASIC_POINTS, MAX_MILESTONES, MILESTONES_SEED, PROPOSAL_SEED,
+    ROLE_SEED, SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: SetMilestonesParams)]
+pub struct SetMilestones<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(
+        init,
+        payer = payer,
+        space = MilestonePlan::SPACE,
+        seeds = [MILESTONES_SEED.as_bytes(), agreement.key().as_ref()],
+        bump
+    )]
+    pub milestone_plan: Account<'info, MilestonePlan>,
+    #[account(
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), RoleKind::Operator.seed().as_ref(), payer.key().as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Option<Account<'info, Role>>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct SetMilestonesParams {
+    pub milestones: Vec<MilestoneTerms>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct MilestoneTerms {
+    /// Share of the proceeds, all milestones add up to `BASIC_POINTS`.
+    pub basis_points: u16,
+    pub approver: Pubkey,
+}
+
+impl<'info> SetMilestones<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: SetMilestonesParams,
+    ) -> Result<()> {
+        if ctx.accounts.settings.multisig_enabled() {
+            let action = AdminAction::SetMilestones {
+                agreement: ctx.accounts.agreement.key(),
+                params: params.clone(),
+            };
+            ctx.accounts
+                .proposal
+                .as_mut()
+                .ok_or(ErrorCode::ProposalRequired)?
+                .execute(
+                    &ctx.accounts.settings,
+                    &action,
+                    Clock::get()?.unix_timestamp,
+                )?;
+        } else if ctx.accounts.operator.is_none()
+            && ctx.accounts.settings.owner != *ctx.accounts.payer.key
+        {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+        let agreement = &ctx.accounts.agreement;
+        if agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if agreement.hold_duration_start != 0 {
+            return Err(ErrorCode::FundingCompleted.into());
+        }
+        if agreement.terms_signed() {
+            return Err(ErrorCode::AgreementAlreadyAccepted.into());
+        }
+        if agreement.payment_mints_count > 0 {
+            return Err(ErrorCode::InvalidPaymentMint.into());
+        }
+        if params.milestones.is_empty()
+            || params.milestones.len() > MAX_MILESTONES
+            || params.milestones.iter().any(|milestone| {
+                milestone.basis_points == 0 || milestone.approver == Pubkey::default()
+            })
+            || params
+                .milestones
+                .iter()
+                .map(|milestone| milestone.basis_points as u64)
+                .sum::<u64>()
+                != BASIC_POINTS
+        {
+            return Err(ErrorCode::InvalidMilestones.into());
+        }
+
+        let milestone_plan = &mut ctx.accounts.milestone_plan;
+        milestone_plan.agreement = agreement.key();
+        milestone_plan.milestones = params
+            .milestones
+            .iter()
+            .map(|terms| Milestone {
+                basis_points: terms.basis_points,
+                approver: terms.approver,
+                approved: false,
+            })
+            .collect();
+        milestone_plan.bump = ctx.bumps.milestone_plan;
+        milestone_plan.version = MilestonePlan::VERSION;
+
+        ctx.accounts.agreement.milestones_count = params.milestones.len() as u8;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{spl_token::native_mint, Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    error::ErrorCode, utils::unwrap_native, Agreement, BlockedWallet, CommitmentSettled, Investor,
+    PayoutEscrowed, AGREEMENT_SEED, PAYMENT_SEED, UNWRAP_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: SettleCommitmentsParams)]
+pub struct SettleCommitments<'info> {
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(mut,
+        seeds = [PAYMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub payment_token_account: Account<'info, TokenAccount>,
+    /// Set to refund in lamports from a native mint vault, see `SettleCommitments::handle`.
+    #[account(address = native_mint::ID @ ErrorCode::InvalidPaymentMint)]
+    pub native_mint: Option<Account<'info, Mint>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct SettleCommitmentsParams;
+
+impl<'info> SettleCommitments<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: SettleCommitmentsParams,
+    ) -> Result<()> {
+        let agreement = &ctx.accounts.agreement;
+        if agreement.commit_deadline == 0 {
+            return Err(ErrorCode::CommitWindowNotEnabled.into());
+        }
+        if agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        if agreement.hold_duration_start != 0 {
+            return Err(ErrorCode::FundingCompleted.into());
+        }
+        if ctx.accounts.clock.unix_timestamp <= agreement.commit_deadline {
+            return Err(ErrorCode::CommitWindowOpen.into());
+        }
+        if agreement.total_committed < agreement.expected_payment {
+            return Err(ErrorCode::CommitmentsBelowTarget.into());
+        }
+
+        let mut success_count = 0;
+
+        // Each investor comes with its payment token account for the refund and its blocklist PDA,
+        // a blocked investor's refund stays escrowed in the vault. Lamport refunds pass the
+        // investor wallet instead of the token account, followed by its unwrap account.
+        let chunk_size = if ctx.accounts.native_mint.is_some() {
+            4
+        } else {
+            3
+        };
+        for accounts in ctx.remaining_accounts.chunks(chunk_size) {
+            if accounts.len() != chunk_size {
+                return Err(ErrorCode::InvalidInvestorAccount.into());
+            }
+            let investor_account = &accounts[0];
+
+            let mut investor = Account::<Investor>::try_from(investor_account)?;
+            if investor.agreement != ctx.accounts.agreement.key() {
+                return Err(ErrorCode::InvalidInvestorAccount.into());
+            }
+            if investor.settled {
+                continue;
+            }
+
+            let agreement = &ctx.accounts.agreement;
+            let committed = investor.required_amount;
+            let amount = (committed as u128 * agreement.expected_payment as u128
+                / agreement.total_committed as u128) as u64;
+            let tokens = (committed as u128 * agreement.expected_tokens as u128
+                / agreement.total_committed as u128) as u64;
+            let refund = committed - amount;
+
+            if refund > 0
+                && BlockedWallet::is_blocked(
+                    &accounts[2],
+                    &agreement.tenant_seed(),
+                    &investor.wallet,
+                )?
+            {
+                investor.escrowed_refund = refund;
+                emit!(PayoutEscrowed {
+                    agreement: agreement.key(),
+                    wallet: investor.wallet,
+                    amount: refund,
+                });
+            } else if refund > 0 && ctx.accounts.native_mint.is_some() {
+                if *accounts[1].key != investor.wallet {
+                    return Err(ErrorCode::InvalidDestination.into());
+                }
+                let (_, unwrap_bump) = Pubkey::find_program_address(
+                    &[
+                        UNWRAP_SEED.as_bytes(),
+                        agreement.key().as_ref(),
+                        investor.wallet.as_ref(),
+                    ],
+                    ctx.program_id,
+                );
+                unwrap_native(
+                    agreement,
+                    &ctx.accounts.payment_token_account,
+                    &ctx.accounts
+                        .native_mint
+                        .as_ref()
+                        .ok_or(ErrorCode::InvalidPaymentMint)?
+                        .to_account_info(),
+                    &accounts[3],
+                    unwrap_bump,
+                    &accounts[1],
+                    &ctx.accounts.payer.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    &ctx.accounts.token_program.to_account_info(),
+                    refund,
+                )?;
+            } else if refund > 0 {
+                let investor_token_account = Account::<TokenAccount>::try_from(&accounts[1])?;
+                if investor_token_account.owner != investor.wallet
+                    || investor_token_account.mint != agreement.payment_token_mint
+                {
+                    return Err(ErrorCode::InvalidDestination.into());
+                }
+
+                anchor_spl::token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.payment_token_account.to_account_info(),
+                            to: accounts[1].to_account_info(),
+                            authority: agreement.to_account_info(),
+                        },
+                        &[&[
+                            AGREEMENT_SEED.as_bytes(),
+                            agreement.tenant_seed().as_ref(),
+                            agreement.agreement_id.to_le_bytes().as_ref(),
+                            &[agreement.bump],
+                        ]],
+                    ),
+                    refund,
+                )?;
+            }
+
+            investor.required_amount = amount;
+            investor.token_allocation = tokens;
+            investor.settled = true;
+            investor.exit(ctx.program_id)?;
+
+            let agreement = &mut ctx.accounts.agreement;
+            agreement.total_invested -= refund;
+            agreement.total_required -= refund;
+            agreement.total_token_allocation += tokens;
+            agreement.tokens_sold += tokens;
+            agreement.settled_count += 1;
+            success_count += 1;
+
+            emit!(CommitmentSettled {
+                agreement: agreement.key(),
+                wallet: investor.wallet,
+                amount,
+                tokens,
+                refund,
+            });
+        }
+
+        if success_count == 0 {
+            return Err(ErrorCode::NotAnInvestor.into());
+        }
+
+        // The round enters the hold once every commitment is settled
+        let agreement = &mut ctx.accounts.agreement;
+        if agreement.settled_count == agreement.investors_count {
+            agreement.hold_duration_start = ctx.accounts.clock.unix_timestamp;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, Agreement, Role, RoleKind, Settings, WaitlistEntry, WaitlistEntrySkipped,
+    AGREEMENT_SEED, ROLE_SEED, SETTINGS_SEED, WAITLIST_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: SkipWaitlistEntryParams)]
+pub struct SkipWaitlistEntry<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    /// Head of the waitlist.
+    #[account(mut,
+        close = agreement_owner,
+        seeds = [WAITLIST_SEED.as_bytes(), agreement.key().as_ref(), waitlist_entry.wallet.as_ref()],
+        bump = waitlist_entry.bump,
+        constraint = waitlist_entry.position == agreement.waitlist_head @ ErrorCode::InvalidWaitlistEntry,
+    )]
+    pub waitlist_entry: Account<'info, WaitlistEntry>,
+    /// CHECK: Paid the rent of the closed entry, receives it back.
+    #[account(mut,
+        address = agreement.owner @ ErrorCode::InvalidDestination,
+    )]
+    pub agreement_owner: UncheckedAccount<'info>,
+    #[account(
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), RoleKind::Operator.seed().as_ref(), payer.key().as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Option<Account<'info, Role>>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct SkipWaitlistEntryParams;
+
+impl<'info> SkipWaitlistEntry<'info> {
+    /// Removes the head of the waitlist, for a wallet that got an allocation since it joined or
+    /// should no longer be served, so `expire_allocation` moves on to the next one.
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: SkipWaitlistEntryParams,
+    ) -> Result<()> {
+        if ctx.accounts.operator.is_none() && ctx.accounts.settings.owner != *ctx.accounts.payer.key
+        {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+
+        ctx.accounts.agreement.waitlist_head += 1;
+
+        emit!(WaitlistEntrySkipped {
+            agreement: ctx.accounts.agreement.key(),
+            wallet: ctx.accounts.waitlist_entry.wallet,
+            position: ctx.accounts.waitlist_entry.position,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+use crate::{
+    error::ErrorCode, Agreement, Role, RoleKind, SellerBondSlashed, Settings, AGREEMENT_SEED,
+    BOND_SEED, ROLE_SEED, SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: SlashSellerBondParams)]
+pub struct SlashSellerBond<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(mut,
+        seeds = [BOND_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub bond_token_account: Account<'info, TokenAccount>,
+    /// Receives the bond unless it goes to funded investors.
+    #[account(mut)]
+    pub company_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [ROLE_SEED.as_bytes(), settings.key().as_ref(), RoleKind::Operator.seed().as_ref(), payer.key().as_ref()],
+        bump = operator.bump,
+    )]
+    pub operator: Option<Account<'info, Role>>,
+    /// Owner or operator, the slash fixes the investors sharing the bond.
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct SlashSellerBondParams;
+
+impl<'info> SlashSellerBond<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: SlashSellerBondParams,
+    ) -> Result<()> {
+        if ctx.accounts.operator.is_none() && ctx.accounts.settings.owner != *ctx.accounts.payer.key
+        {
+            return Err(ErrorCode::NotAuthorized.into());
+        }
+
+        // An agreement cancelled before the seller was late returns the bond through
+        // `reclaim_seller_bond`
+        let agreement = &ctx.accounts.agreement;
+        if !agreement.bond_slashable(ctx.accounts.clock.unix_timestamp) {
+            return Err(ErrorCode::SellerBondNotSlashable.into());
+        }
+
+        // Investors claim their share through `claim_bond_share`, `sweep_bond_shares` pays what
+        // is left unclaimed after `bond_claim_window` to the company
+        let to_investors = agreement.bond_to_investors && agreement.total_invested > 0;
+        if !to_investors {
+            let company_token_account = ctx
+                .accounts
+                .company_token_account
+                .as_ref()
+                .ok_or(ErrorCode::InvalidDestination)?;
+            if company_token_account.owner != agreement.company_wallet
+                || company_token_account.mint != agreement.payment_token_mint
+            {
+                return Err(ErrorCode::InvalidDestination.into());
+            }
+
+            anchor_spl::token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.bond_token_account.to_account_info(),
+                        to: company_token_account.to_account_info(),
+                        authority: agreement.to_account_info(),
+                    },
+                    &[&[
+                        AGREEMENT_SEED.as_bytes(),
+                        agreement.tenant_seed().as_ref(),
+                        agreement.agreement_id.to_le_bytes().as_ref(),
+                        &[agreement.bump],
+                    ]],
+                ),
+                agreement.seller_bond,
+            )?;
+        }
+
+        let agreement = &mut ctx.accounts.agreement;
+        agreement.bond_slashed = true;
+        agreement.bond_slashed_at = ctx.accounts.clock.unix_timestamp;
+        if to_investors {
+            agreement.bond_slash_base = agreement.total_invested;
+        }
+
+        emit!(SellerBondSlashed {
+            agreement: agreement.key(),
+            amount: agreement.seller_bond,
+            to_investors,
+        });
+
+        Ok(())
+    }
+}
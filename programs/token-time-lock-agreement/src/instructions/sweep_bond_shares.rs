@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+use crate::{error::ErrorCode, Agreement, BondSharesSwept, AGREEMENT_SEED, BOND_SEED};
+
+#[derive(Accounts)]
+#[instruction(params: SweepBondSharesParams)]
+pub struct SweepBondShares<'info> {
+    #[account(
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(mut,
+        seeds = [BOND_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub bond_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub company_token_account: Account<'info, TokenAccount>,
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct SweepBondSharesParams;
+
+impl<'info> SweepBondShares<'info> {
+    /// Pays the bond shares left unclaimed after `bond_claim_window` to the company, covering
+    /// investors who withdrew, were settled down or stay blocked, and the rounding dust.
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: SweepBondSharesParams,
+    ) -> Result<()> {
+        let agreement = &ctx.accounts.agreement;
+        if !agreement.bond_slashed || agreement.bond_slash_base == 0 {
+            return Err(ErrorCode::BondNotSlashedToInvestors.into());
+        }
+        if ctx.accounts.clock.unix_timestamp
+            <= agreement.bond_slashed_at + agreement.bond_claim_window
+        {
+            return Err(ErrorCode::BondClaimWindowOpen.into());
+        }
+        if ctx.accounts.company_token_account.owner != agreement.company_wallet
+            || ctx.accounts.company_token_account.mint != agreement.payment_token_mint
+        {
+            return Err(ErrorCode::InvalidDestination.into());
+        }
+        let amount = ctx.accounts.bond_token_account.amount;
+        if amount == 0 {
+            return Err(ErrorCode::NoFundsToWithdraw.into());
+        }
+
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.bond_token_account.to_account_info(),
+                    to: ctx.accounts.company_token_account.to_account_info(),
+                    authority: agreement.to_account_info(),
+                },
+                &[&[
+                    AGREEMENT_SEED.as_bytes(),
+                    agreement.tenant_seed().as_ref(),
+                    agreement.agreement_id.to_le_bytes().as_ref(),
+                    &[agreement.bump],
+                ]],
+            ),
+            amount,
+        )?;
+
+        emit!(BondSharesSwept {
+            agreement: agreement.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    AdminAction, BlockedWallet, Proposal, Settings, WalletUnblocked, BLOCKLIST_SEED, PROPOSAL_SEED,
+    SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: UnblockWalletParams)]
+pub struct UnblockWallet<'info> {
+    #[account(
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        close = payer,
+        seeds = [BLOCKLIST_SEED.as_bytes(), settings.tenant_seed().as_ref(), params.wallet.as_ref()],
+        bump = blocked_wallet.bump,
+    )]
+    pub blocked_wallet: Account<'info, BlockedWallet>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct UnblockWalletParams {
+    pub wallet: Pubkey,
+}
+
+impl<'info> UnblockWallet<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: UnblockWalletParams,
+    ) -> Result<()> {
+        AdminAction::UnblockWallet {
+            params: params.clone(),
+        }
+        .authorize(
+            &ctx.accounts.settings,
+            ctx.accounts.proposal.as_mut(),
+            ctx.accounts.payer.key,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit!(WalletUnblocked {
+            settings: ctx.accounts.settings.key(),
+            wallet: params.wallet,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, AdminAction, FeeShare, Proposal, Settings, BASIC_POINTS, MAX_ALLOWED_MINTS,
+    MAX_MULTISIG_SIGNERS, PROPOSAL_SEED, SETTINGS_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: UpdateSettingsParams)]
+pub struct UpdateSettings<'info> {
+    #[account(mut,
+        seeds = [SETTINGS_SEED.as_bytes(), settings.tenant_seed().as_ref()],
+        bump
+    )]
+    pub settings: Account<'info, Settings>,
+    #[account(mut,
+        seeds = [PROPOSAL_SEED.as_bytes(), settings.key().as_ref(), proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateSettingsParams {
+    pub cancel_timeout: Option<i64>,
+    pub commission_basis_points: Option<u16>,
+    pub owner: Option<Pubkey>,
+    pub allowed_mints: Option<Vec<Pubkey>>,
+    pub treasury: Option<Pubkey>,
+    pub multisig_signers: Option<Vec<Pubkey>>,
+    pub multisig_threshold: Option<u8>,
+    /// Commission recipients of agreements created from now on, empty pays the treasury.
+    pub fee_split: Option<Vec<FeeShare>>,
+}
+
+impl<'info> UpdateSettings<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        params: UpdateSettingsParams,
+    ) -> Result<()> {
+        AdminAction::UpdateSettings {
+            params: params.clone(),
+        }
+        .authorize(
+            &ctx.accounts.settings,
+            ctx.accounts.proposal.as_mut(),
+            ctx.accounts.payer.key,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let settings = &mut ctx.accounts.settings;
+
+        if let Some(cancel_timeout) = params.cancel_timeout {
+            settings.cancel_timeout = cancel_timeout;
+        }
+        if let Some(commission_basis_points) = params.commission_basis_points {
+            if commission_basis_points as u64 > BASIC_POINTS {
+                return Err(ErrorCode::InvalidCommission.into());
+            }
+            settings.commission_basis_points = commission_basis_points;
+        }
+        if let Some(owner) = params.owner {
+            settings.owner = owner;
+        }
+        if let Some(allowed_mints) = params.allowed_mints {
+            if allowed_mints.len() > MAX_ALLOWED_MINTS {
+                return Err(ErrorCode::TooManyAllowedMints.into());
+            }
+            settings.allowed_mints = allowed_mints;
+        }
+        if let Some(treasury) = params.treasury {
+            settings.treasury = treasury;
+        }
+        if let Some(fee_split) = params.fee_split {
+            if !FeeShare::valid_split(&fee_split) {
+                return Err(ErrorCode::InvalidFeeSplit.into());
+            }
+            settings.fee_split = fee_split;
+        }
+        if let Some(multisig_signers) = params.multisig_signers {
+            settings.multisig_signers = multisig_signers;
+        }
+        if let Some(multisig_threshold) = params.multisig_threshold {
+            settings.multisig_threshold = multisig_threshold;
+        }
+        let mut signers = settings.multisig_signers.clone();
+        signers.sort();
+        signers.dedup();
+        if settings.multisig_signers.len() > MAX_MULTISIG_SIGNERS
+            || signers.len() != settings.multisig_signers.len()
+            || settings.multisig_threshold as usize > settings.multisig_signers.len()
+        {
+            return Err(ErrorCode::InvalidMultisigConfig.into());
+        }
+
+        Ok(())
+    }
+}
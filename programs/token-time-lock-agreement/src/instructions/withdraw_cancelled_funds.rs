@@ -1,16 +1,16 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Transfer};
+use anchor_spl::token::{spl_token::native_mint, Mint, Token, TokenAccount, Transfer};
 
 use crate::{
-    error::ErrorCode, Agreement, Investor, AGREEMENT_SEED, INVESTOR_SEED, PAYMENT_SEED,
-    PROJECT_SEED,
+    error::ErrorCode, utils::unwrap_native, Agreement, BlockedWallet, Investor, PayoutEscrowed,
+    AGREEMENT_SEED, BLOCKLIST_SEED, INVESTOR_SEED, PAYMENT_SEED, PROJECT_SEED, UNWRAP_SEED,
 };
 
 #[derive(Accounts)]
 #[instruction(params: WithdrawCancelledFundsParams)]
 pub struct WithdrawCancelledFunds<'info> {
     #[account(mut,
-        seeds = [AGREEMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
@@ -20,12 +20,32 @@ pub struct WithdrawCancelledFunds<'info> {
     )]
     pub investor: Option<Account<'info, Investor>>,
     #[account(mut,
-        seeds = [investor.as_ref().map(|_| PAYMENT_SEED).unwrap_or(PROJECT_SEED).as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [investor.as_ref().map(|_| PAYMENT_SEED).unwrap_or(PROJECT_SEED).as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref(), investor.as_ref().map(|investor| investor.payment_mint_seed()).unwrap_or_default().as_ref()],
         bump,
     )]
     pub agreement_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Blocklist PDA of the payee, blocked payouts stay escrowed in the vault.
+    #[account(
+        seeds = [BLOCKLIST_SEED.as_bytes(), agreement.tenant_seed().as_ref(), investor.as_ref().map(|investor| investor.wallet).unwrap_or(agreement.token_seller).as_ref()],
+        bump,
+    )]
+    pub blocked_wallet: UncheckedAccount<'info>,
+    /// `None` pays the payee in lamports from a native mint vault.
     #[account(mut)]
-    pub destination_token_account: Account<'info, TokenAccount>,
+    pub destination_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Temporary token account created and closed to unwrap the payout.
+    #[account(mut,
+        seeds = [UNWRAP_SEED.as_bytes(), agreement.key().as_ref(), investor.as_ref().map(|investor| investor.wallet).unwrap_or(agreement.token_seller).as_ref()],
+        bump,
+    )]
+    pub unwrap_token_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID @ ErrorCode::InvalidPaymentMint)]
+    pub native_mint: Option<Account<'info, Mint>>,
+    /// CHECK: Payee wallet receiving the unwrapped payout.
+    #[account(mut,
+        address = investor.as_ref().map(|investor| investor.wallet).unwrap_or(agreement.token_seller) @ ErrorCode::InvalidDestination,
+    )]
+    pub destination: Option<UncheckedAccount<'info>>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -46,56 +66,115 @@ impl<'info> WithdrawCancelledFunds<'info> {
         }
 
         if ctx.accounts.agreement.token_seller == *ctx.accounts.payer.key {
-            let remaining_tokens = ctx.accounts.agreement_token_account.amount;
+            // Tokens escrowed for the seller or still owed to investors stay in the vault
+            let remaining_tokens = ctx
+                .accounts
+                .agreement_token_account
+                .amount
+                .checked_sub(ctx.accounts.agreement.seller_escrowed_tokens)
+                .and_then(|amount| amount.checked_sub(ctx.accounts.agreement.tokens_owed))
+                .ok_or(ErrorCode::Overflow)?;
             if remaining_tokens > 0 {
-                anchor_spl::token::transfer(
-                    CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.agreement_token_account.to_account_info(),
-                            to: ctx.accounts.destination_token_account.to_account_info(),
-                            authority: ctx.accounts.agreement.to_account_info(),
-                        },
-                        &[&[
-                            AGREEMENT_SEED.as_bytes(),
-                            ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
-                            &[ctx.accounts.agreement.bump],
-                        ]],
-                    ),
-                    remaining_tokens,
-                )?;
+                if BlockedWallet::is_blocked(
+                    &ctx.accounts.blocked_wallet,
+                    &ctx.accounts.agreement.tenant_seed(),
+                    &ctx.accounts.agreement.token_seller,
+                )? {
+                    ctx.accounts.agreement.seller_escrowed_tokens += remaining_tokens;
+                    emit!(PayoutEscrowed {
+                        agreement: ctx.accounts.agreement.key(),
+                        wallet: ctx.accounts.agreement.token_seller,
+                        amount: remaining_tokens,
+                    });
+                } else {
+                    ctx.accounts
+                        .pay_out(remaining_tokens, ctx.bumps.unwrap_token_account)?;
+                }
 
                 // Reset sellerDeposited flag if it was set
                 ctx.accounts.agreement.seller_deposited = false;
             } else {
                 return Err(ErrorCode::NoFundsToWithdraw.into());
             }
-        } else if let Some(investor) = &ctx.accounts.investor {
+        } else if let Some(investor) = &mut ctx.accounts.investor {
             if !investor.investor_deposited {
                 return Err(ErrorCode::NoFundsToWithdraw.into());
             }
 
-            let invested_amount = investor.required_amount;
-            anchor_spl::token::transfer(
+            let invested_amount = ctx.accounts.agreement.refundable(investor.refund_amount());
+            investor.investor_deposited = false;
+            investor.refunded = true;
+            if BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+                investor.escrowed_amount = invested_amount;
+                emit!(PayoutEscrowed {
+                    agreement: ctx.accounts.agreement.key(),
+                    wallet: investor.wallet,
+                    amount: invested_amount,
+                });
+            } else {
+                ctx.accounts
+                    .pay_out(invested_amount, ctx.bumps.unwrap_token_account)?;
+            }
+        } else {
+            return Err(ErrorCode::NotAnInvestor.into());
+        }
+
+        Ok(())
+    }
+
+    /// Transfers `amount` from the vault to the destination token account, or unwraps it to the
+    /// destination wallet.
+    fn pay_out(&self, amount: u64, unwrap_bump: Option<u8>) -> Result<()> {
+        let payee = self
+            .investor
+            .as_ref()
+            .map(|investor| investor.wallet)
+            .unwrap_or(self.agreement.token_seller);
+        match &self.destination_token_account {
+            Some(destination_token_account) if destination_token_account.owner != payee => {
+                Err(ErrorCode::InvalidDestination.into())
+            }
+            Some(destination_token_account) => anchor_spl::token::transfer(
                 CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
+                    self.token_program.to_account_info(),
                     Transfer {
-                        from: ctx.accounts.agreement_token_account.to_account_info(),
-                        to: ctx.accounts.destination_token_account.to_account_info(),
-                        authority: ctx.accounts.agreement.to_account_info(),
+                        from: self.agreement_token_account.to_account_info(),
+                        to: destination_token_account.to_account_info(),
+                        authority: self.agreement.to_account_info(),
                     },
                     &[&[
                         AGREEMENT_SEED.as_bytes(),
-                        ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
-                        &[ctx.accounts.agreement.bump],
+                        self.agreement.tenant_seed().as_ref(),
+                        self.agreement.agreement_id.to_le_bytes().as_ref(),
+                        &[self.agreement.bump],
                     ]],
                 ),
-                invested_amount,
-            )?;
-        } else {
-            return Err(ErrorCode::NotAnInvestor.into());
+                amount,
+            ),
+            None => unwrap_native(
+                &self.agreement,
+                &self.agreement_token_account,
+                &self
+                    .native_mint
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidPaymentMint)?
+                    .to_account_info(),
+                &self
+                    .unwrap_token_account
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidDestination)?
+                    .to_account_info(),
+                unwrap_bump.unwrap_or_default(),
+                &self
+                    .destination
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidDestination)?
+                    .to_account_info(),
+                &self.payer.to_account_info(),
+                &self.system_program.to_account_info(),
+                &self.token_program.to_account_info(),
+                amount,
+            ),
         }
-
-        Ok(())
     }
 }
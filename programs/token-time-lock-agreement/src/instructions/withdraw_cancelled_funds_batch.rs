@@ -1,18 +1,21 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount, Transfer};
 
-use crate::{error::ErrorCode, Agreement, Investor, AGREEMENT_SEED, PAYMENT_SEED};
+use crate::{
+    error::ErrorCode, Agreement, BlockedWallet, Investor, PayoutEscrowed, AGREEMENT_SEED,
+    PAYMENT_SEED,
+};
 
 #[derive(Accounts)]
 #[instruction(params: WithdrawCancelledFundsBatchParams)]
 pub struct WithdrawCancelledFundsBatch<'info> {
     #[account(mut,
-        seeds = [AGREEMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
     #[account(mut,
-        seeds = [PAYMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [PAYMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump,
     )]
     pub payment_token_account: Account<'info, TokenAccount>,
@@ -36,39 +39,65 @@ impl<'info> WithdrawCancelledFundsBatch<'info> {
         }
 
         let mut success_count = 0;
-        for accounts in ctx.remaining_accounts.chunks(2) {
+        // Each investor comes with its token account and blocklist PDA
+        for accounts in ctx.remaining_accounts.chunks(3) {
+            if accounts.len() != 3 {
+                return Err(ErrorCode::InvalidInvestorAccount.into());
+            }
             let investor_account = &accounts[0];
-            let investor_token_account = &accounts[1];
 
             // decode investor account
-            let mut investor = Investor::try_from_slice(&investor_account.data.borrow())?;
+            let mut investor = Account::<Investor>::try_from(investor_account)?;
             if investor.agreement != ctx.accounts.agreement.key() {
                 return Err(ErrorCode::InvalidInvestorAccount.into());
             }
+            let investor_token_account = Account::<TokenAccount>::try_from(&accounts[1])?;
+            if investor_token_account.owner != investor.wallet
+                || investor_token_account.mint != ctx.accounts.payment_token_account.mint
+            {
+                return Err(ErrorCode::InvalidDestination.into());
+            }
 
-            if !investor.investor_deposited {
+            // Deposits in other payment mints are refunded from their own vault by `withdraw_cancelled_funds`
+            if !investor.investor_deposited || investor.payment_mint != Pubkey::default() {
                 continue;
             }
 
-            let invested_amount = investor.required_amount;
-            anchor_spl::token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.payment_token_account.to_account_info(),
-                        to: investor_token_account.to_account_info(),
-                        authority: ctx.accounts.agreement.to_account_info(),
-                    },
-                    &[&[
-                        AGREEMENT_SEED.as_bytes(),
-                        ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
-                        &[ctx.accounts.agreement.bump],
-                    ]],
-                ),
-                invested_amount,
-            )?;
+            let invested_amount = ctx.accounts.agreement.refundable(investor.required_amount);
+            if BlockedWallet::is_blocked(
+                &accounts[2],
+                &ctx.accounts.agreement.tenant_seed(),
+                &investor.wallet,
+            )? {
+                investor.escrowed_amount = invested_amount;
+                emit!(PayoutEscrowed {
+                    agreement: ctx.accounts.agreement.key(),
+                    wallet: investor.wallet,
+                    amount: invested_amount,
+                });
+            } else {
+                anchor_spl::token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.payment_token_account.to_account_info(),
+                            to: investor_token_account.to_account_info(),
+                            authority: ctx.accounts.agreement.to_account_info(),
+                        },
+                        &[&[
+                            AGREEMENT_SEED.as_bytes(),
+                            ctx.accounts.agreement.tenant_seed().as_ref(),
+                            ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
+                            &[ctx.accounts.agreement.bump],
+                        ]],
+                    ),
+                    invested_amount,
+                )?;
+            }
 
             investor.investor_deposited = false;
+            investor.refunded = true;
+            investor.exit(ctx.program_id)?;
             success_count += 1;
         }
 
@@ -7,12 +7,12 @@ use crate::{error::ErrorCode, Agreement, AGREEMENT_SEED, PROJECT_SEED};
 #[instruction(params: WithdrawExcessTokensParams)]
 pub struct WithdrawExcessTokens<'info> {
     #[account(mut,
-        seeds = [AGREEMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
     #[account(mut,
-        seeds = [PROJECT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [PROJECT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump,
     )]
     pub project_token_account: Account<'info, TokenAccount>,
@@ -63,6 +63,7 @@ impl<'info> WithdrawExcessTokens<'info> {
                     },
                     &[&[
                         AGREEMENT_SEED.as_bytes(),
+                        ctx.accounts.agreement.tenant_seed().as_ref(),
                         ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
                         &[ctx.accounts.agreement.bump],
                     ]],
@@ -1,33 +1,64 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Transfer};
+use anchor_spl::token::{spl_token::native_mint, Mint, Token, TokenAccount, Transfer};
 
 use crate::{
-    error::ErrorCode, Agreement, Settings, AGREEMENT_SEED, BASIC_POINTS, PAYMENT_SEED,
-    SETTINGS_SEED,
+    error::ErrorCode,
+    utils::{pay_commission, unwrap_native},
+    Agreement, BlockedWallet, PaymentMint, PayoutEscrowed, RevenueSharePaid, Settings,
+    AGREEMENT_SEED, BASIC_POINTS, BLOCKLIST_SEED, PAYMENT_MINT_SEED, PAYMENT_SEED, SETTINGS_SEED,
+    UNWRAP_SEED,
 };
 
 #[derive(Accounts)]
 #[instruction(params: WithdrawFundsParams)]
 pub struct WithdrawFunds<'info> {
     #[account(
-        seeds = [SETTINGS_SEED.as_bytes()],
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
         bump
     )]
     pub settings: Account<'info, Settings>,
     #[account(mut,
-        seeds = [AGREEMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
+    /// Additional mint to pay out, `None` pays out `payment_token_mint`.
     #[account(mut,
-        seeds = [PAYMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [PAYMENT_MINT_SEED.as_bytes(), agreement.key().as_ref(), payment_mint.mint.as_ref()],
+        bump = payment_mint.bump,
+    )]
+    pub payment_mint: Option<Account<'info, PaymentMint>>,
+    #[account(mut,
+        seeds = [PAYMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref(), payment_mint.as_ref().map(|payment_mint| payment_mint.mint.as_ref()).unwrap_or_default()],
         bump,
     )]
     pub payment_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Blocklist PDA of the seller, blocked payouts stay escrowed in the vault.
+    #[account(
+        seeds = [BLOCKLIST_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.token_seller.as_ref()],
+        bump,
+    )]
+    pub blocked_wallet: UncheckedAccount<'info>,
+    /// `None` pays the seller in lamports from a native mint vault, unused with a revenue split.
     #[account(mut)]
-    pub seller_token_account: Account<'info, TokenAccount>,
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Temporary token account created and closed to unwrap the payout.
+    #[account(mut,
+        seeds = [UNWRAP_SEED.as_bytes(), agreement.key().as_ref(), agreement.token_seller.as_ref()],
+        bump,
+    )]
+    pub unwrap_token_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID @ ErrorCode::InvalidPaymentMint)]
+    pub native_mint: Option<Account<'info, Mint>>,
+    /// CHECK: Seller wallet receiving the unwrapped payout.
+    #[account(mut,
+        address = agreement.token_seller @ ErrorCode::InvalidDestination,
+    )]
+    pub token_seller: Option<UncheckedAccount<'info>>,
+    /// Treasury token account, `None` when the agreement splits its commission and no fee
+    /// recipient is blocked.
     #[account(mut)]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -43,65 +74,229 @@ impl<'info> WithdrawFunds<'info> {
         ctx: &mut Context<'_, '_, 'info, 'info, Self>,
         _: WithdrawFundsParams,
     ) -> Result<()> {
-        if ctx.accounts.agreement.agreement_cancelled {
+        // A terminated milestone agreement still pays out what was approved before termination
+        let terminated = ctx.accounts.agreement.agreement_cancelled;
+        if terminated
+            && (ctx.accounts.agreement.milestones_count == 0
+                || ctx.accounts.agreement.hold_duration_start == 0)
+        {
             return Err(ErrorCode::AgreementAlreadyCancelled.into());
         }
-        if ctx.accounts.agreement.total_invested != ctx.accounts.agreement.expected_payment {
-            return Err(ErrorCode::FullAmountNotCollected.into());
+        let raised_amount = ctx
+            .accounts
+            .agreement
+            .raised_amount()
+            .ok_or(ErrorCode::FullAmountNotCollected)?;
+        if let Some(seller_token_account) = &ctx.accounts.seller_token_account {
+            if ctx.accounts.agreement.token_seller != seller_token_account.owner.key() {
+                return Err(ErrorCode::InvalidDestination.into());
+            }
         }
-        if ctx.accounts.agreement.funds_commision_collected {
-            return Err(ErrorCode::FundCommissionAlreadyCollected.into());
+
+        // Other mints pay out their whole vault, `payment_token_mint` the rest of the raise
+        let (proceeds, released) = match &ctx.accounts.payment_mint {
+            Some(payment_mint) => {
+                if payment_mint.funds_withdrawn {
+                    return Err(ErrorCode::FundCommissionAlreadyCollected.into());
+                }
+                // The seller escrow is kept in `payment_token_mint` only
+                if BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+                    return Err(ErrorCode::WalletBlocked.into());
+                }
+                // Milestone agreements take `payment_token_mint` only
+                (ctx.accounts.payment_token_account.amount, 0)
+            }
+            None => {
+                if ctx.accounts.agreement.funds_commision_collected {
+                    return Err(ErrorCode::FundCommissionAlreadyCollected.into());
+                }
+                // Escrowed seller payouts sit in the payment vault, which a cancellation repurposes
+                if terminated && BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+                    return Err(ErrorCode::WalletBlocked.into());
+                }
+                (
+                    raised_amount - ctx.accounts.agreement.secondary_invested,
+                    ctx.accounts.agreement.funds_released,
+                )
+            }
+        };
+
+        // Milestone agreements release the approved share only
+        let release_amount = (proceeds as u128 * ctx.accounts.agreement.released_bps() as u128
+            / BASIC_POINTS as u128) as u64
+            - released;
+        if release_amount == 0 {
+            return Err(ErrorCode::NoFundsToWithdraw.into());
         }
-        if ctx.accounts.agreement.token_seller != ctx.accounts.seller_token_account.owner.key() {
-            return Err(ErrorCode::InvalidDestination.into());
+
+        let commission_amount =
+            (release_amount * ctx.accounts.settings.commission_basis_points as u64) / BASIC_POINTS;
+        let seller_amount = release_amount - commission_amount;
+        let fully_released = released + release_amount == proceeds;
+        match &mut ctx.accounts.payment_mint {
+            Some(payment_mint) => payment_mint.funds_withdrawn = true,
+            None => {
+                ctx.accounts.agreement.funds_released += release_amount;
+                ctx.accounts.agreement.funds_commision_collected = fully_released;
+            }
         }
-        if ctx.accounts.settings.owner != ctx.accounts.owner_token_account.owner.key() {
-            return Err(ErrorCode::InvalidDestination.into());
+
+        // Fee recipient token accounts and blocklist PDAs come first, then the revenue split's
+        let fee_accounts_count =
+            (ctx.accounts.agreement.fee_split.len() * 2).min(ctx.remaining_accounts.len());
+        let (fee_accounts, split_accounts) = ctx.remaining_accounts.split_at(fee_accounts_count);
+        if commission_amount > 0 {
+            let fees = pay_commission(
+                &ctx.accounts.agreement,
+                &ctx.accounts.payment_token_account,
+                ctx.accounts.settings.treasury(),
+                ctx.accounts.owner_token_account.as_ref(),
+                fee_accounts,
+                &ctx.accounts.token_program.to_account_info(),
+                commission_amount,
+            )?;
+            match &mut ctx.accounts.payment_mint {
+                Some(payment_mint) => {
+                    payment_mint.commission_paid += commission_amount;
+                    for (collected, amount) in payment_mint.fees_collected.iter_mut().zip(fees) {
+                        *collected += amount;
+                    }
+                }
+                None => {
+                    ctx.accounts.agreement.funds_commission_paid += commission_amount;
+                    for (share, amount) in ctx.accounts.agreement.fee_split.iter_mut().zip(fees) {
+                        share.funds_collected += amount;
+                    }
+                }
+            }
         }
 
-        let commission_amount = (ctx.accounts.agreement.expected_payment
-            * ctx.accounts.settings.commission_basis_points as u64)
-            / BASIC_POINTS;
-        let seller_amount = ctx.accounts.agreement.expected_payment - commission_amount;
+        if !ctx.accounts.agreement.revenue_split.is_empty() {
+            let shares_count = ctx.accounts.agreement.revenue_split.len();
+            if split_accounts.len() != shares_count * 2 {
+                return Err(ErrorCode::InvalidRevenueSplit.into());
+            }
+            let tenant_seed = ctx.accounts.agreement.tenant_seed();
+            let mut unpaid_amount = seller_amount;
+            // Each recipient comes with its token account and blocklist PDA, in split order
+            for (index, accounts) in split_accounts.chunks(2).enumerate() {
+                let share = ctx.accounts.agreement.revenue_split[index].clone();
+                // The last recipient takes the rounding remainder
+                let amount = if index + 1 == shares_count {
+                    unpaid_amount
+                } else {
+                    (seller_amount as u128 * share.basis_points as u128 / BASIC_POINTS as u128)
+                        as u64
+                };
+                unpaid_amount -= amount;
 
-        if commission_amount > 0 {
+                // A blocked recipient's share stays in the vault until `release_revenue_share`
+                let blocked =
+                    BlockedWallet::is_blocked(&accounts[1], &tenant_seed, &share.recipient)?;
+                if !blocked {
+                    let recipient_token_account = Account::<TokenAccount>::try_from(&accounts[0])?;
+                    if recipient_token_account.owner != share.recipient
+                        || recipient_token_account.mint != ctx.accounts.payment_token_account.mint
+                    {
+                        return Err(ErrorCode::InvalidDestination.into());
+                    }
+                    anchor_spl::token::transfer(
+                        CpiContext::new_with_signer(
+                            ctx.accounts.token_program.to_account_info(),
+                            Transfer {
+                                from: ctx.accounts.payment_token_account.to_account_info(),
+                                to: recipient_token_account.to_account_info(),
+                                authority: ctx.accounts.agreement.to_account_info(),
+                            },
+                            &[&[
+                                AGREEMENT_SEED.as_bytes(),
+                                tenant_seed.as_ref(),
+                                ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
+                                &[ctx.accounts.agreement.bump],
+                            ]],
+                        ),
+                        amount,
+                    )?;
+                }
+                let (paid, escrowed) = match &mut ctx.accounts.payment_mint {
+                    Some(payment_mint) => {
+                        let payout = &mut payment_mint.revenue_split[index];
+                        (&mut payout.paid, &mut payout.escrowed)
+                    }
+                    None => {
+                        let share = &mut ctx.accounts.agreement.revenue_split[index];
+                        (&mut share.paid, &mut share.escrowed)
+                    }
+                };
+                *if blocked { escrowed } else { paid } += amount;
+
+                if blocked {
+                    emit!(PayoutEscrowed {
+                        agreement: ctx.accounts.agreement.key(),
+                        wallet: share.recipient,
+                        amount,
+                    });
+                    continue;
+                }
+                emit!(RevenueSharePaid {
+                    agreement: ctx.accounts.agreement.key(),
+                    recipient: share.recipient,
+                    mint: ctx.accounts.payment_token_account.mint,
+                    amount,
+                });
+            }
+        } else if BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+            ctx.accounts.agreement.seller_escrowed_amount += seller_amount;
+            emit!(PayoutEscrowed {
+                agreement: ctx.accounts.agreement.key(),
+                wallet: ctx.accounts.agreement.token_seller,
+                amount: seller_amount,
+            });
+        } else if let Some(seller_token_account) = &ctx.accounts.seller_token_account {
             anchor_spl::token::transfer(
                 CpiContext::new_with_signer(
                     ctx.accounts.token_program.to_account_info(),
                     Transfer {
                         from: ctx.accounts.payment_token_account.to_account_info(),
-                        to: ctx.accounts.owner_token_account.to_account_info(),
+                        to: seller_token_account.to_account_info(),
                         authority: ctx.accounts.agreement.to_account_info(),
                     },
                     &[&[
                         AGREEMENT_SEED.as_bytes(),
+                        ctx.accounts.agreement.tenant_seed().as_ref(),
                         ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
                         &[ctx.accounts.agreement.bump],
                     ]],
                 ),
-                commission_amount,
+                seller_amount,
+            )?;
+        } else {
+            unwrap_native(
+                &ctx.accounts.agreement,
+                &ctx.accounts.payment_token_account,
+                &ctx.accounts
+                    .native_mint
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidPaymentMint)?
+                    .to_account_info(),
+                &ctx.accounts
+                    .unwrap_token_account
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidDestination)?
+                    .to_account_info(),
+                ctx.bumps.unwrap_token_account.unwrap_or_default(),
+                &ctx.accounts
+                    .token_seller
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidDestination)?
+                    .to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                seller_amount,
             )?;
-
-            ctx.accounts.agreement.funds_commision_collected = true;
         }
 
-        anchor_spl::token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.payment_token_account.to_account_info(),
-                    to: ctx.accounts.seller_token_account.to_account_info(),
-                    authority: ctx.accounts.agreement.to_account_info(),
-                },
-                &[&[
-                    AGREEMENT_SEED.as_bytes(),
-                    ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
-                    &[ctx.accounts.agreement.bump],
-                ]],
-            ),
-            seller_amount,
-        )?;
-
         Ok(())
     }
 }
@@ -2,20 +2,22 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount, Transfer};
 
 use crate::{
-    error::ErrorCode, Agreement, Investor, Settings, AGREEMENT_SEED, BASIC_POINTS, INVESTOR_SEED,
-    PROJECT_SEED, SETTINGS_SEED,
+    error::ErrorCode,
+    utils::{pay_commission, pay_referral_fee},
+    Agreement, Attestation, BlockedWallet, Investor, PayoutEscrowed, Settings, AGREEMENT_SEED,
+    ATTESTATION_SEED, BASIC_POINTS, BLOCKLIST_SEED, INVESTOR_SEED, PROJECT_SEED, SETTINGS_SEED,
 };
 
 #[derive(Accounts)]
 #[instruction(params: WithdrawTokensParams)]
 pub struct WithdrawTokens<'info> {
     #[account(
-        seeds = [SETTINGS_SEED.as_bytes()],
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
         bump
     )]
     pub settings: Account<'info, Settings>,
     #[account(mut,
-        seeds = [AGREEMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
@@ -24,15 +26,34 @@ pub struct WithdrawTokens<'info> {
         bump,
     )]
     pub investor: Account<'info, Investor>,
+    #[account(
+        seeds = [ATTESTATION_SEED.as_bytes(), agreement.tenant_seed().as_ref(), attestation.wallet.as_ref()],
+        bump = attestation.bump,
+    )]
+    pub attestation: Option<Account<'info, Attestation>>,
+    /// CHECK: Blocklist PDA of the investor, blocked payouts stay escrowed in the vault.
+    #[account(
+        seeds = [BLOCKLIST_SEED.as_bytes(), agreement.tenant_seed().as_ref(), investor.wallet.as_ref()],
+        bump,
+    )]
+    pub blocked_wallet: UncheckedAccount<'info>,
     #[account(mut,
-        seeds = [PROJECT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [PROJECT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump,
     )]
     pub project_token_account: Account<'info, TokenAccount>,
     #[account(mut)]
     pub investor_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Blocklist PDA of the referrer, required with `referrer_token_account` when the
+    /// investor was referred.
+    pub referrer_blocked_wallet: Option<UncheckedAccount<'info>>,
     #[account(mut)]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub referrer_token_account: Option<Account<'info, TokenAccount>>,
+    /// Treasury token account, `None` when the agreement splits its commission across the
+    /// recipient token account and blocklist PDA pairs passed as remaining accounts, and no
+    /// recipient is blocked.
+    #[account(mut)]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -51,73 +72,126 @@ impl<'info> WithdrawTokens<'info> {
         if !ctx.accounts.agreement.seller_deposited {
             return Err(ErrorCode::TokensNotDeposited.into());
         }
-        if !ctx.accounts.investor.investor_deposited {
+        // Investors of a terminated deal claim their tokens before or after their refund
+        if !ctx.accounts.investor.investor_deposited && !ctx.accounts.investor.refunded {
             return Err(ErrorCode::NotAnInvestor.into());
         }
-        if ctx.accounts.agreement.agreement_cancelled {
+        let terminated = ctx.accounts.agreement.terminated;
+        if ctx.accounts.agreement.agreement_cancelled && !terminated {
             return Err(ErrorCode::AgreementAlreadyCancelled.into());
         }
         if ctx.accounts.agreement.hold_duration_start == 0 {
             return Err(ErrorCode::HoldDurationNotStarted.into());
         }
-        if ctx.accounts.clock.unix_timestamp
-            < ctx.accounts.agreement.hold_duration_start + ctx.accounts.agreement.hold_duration
+        if !terminated
+            && ctx.accounts.clock.unix_timestamp
+                < ctx.accounts.agreement.hold_duration_start + ctx.accounts.agreement.hold_duration
         {
             return Err(ErrorCode::HoldDurationPeriodNotExpired.into());
         }
         if ctx.accounts.investor.has_withdrawn_tokens {
             return Err(ErrorCode::TokensAlreadyWithdrawn.into());
         }
-        if ctx.accounts.settings.owner != ctx.accounts.owner_token_account.owner {
-            return Err(ErrorCode::InvalidDestination.into());
+        if ctx.accounts.agreement.compliance_required {
+            ctx.accounts
+                .attestation
+                .as_ref()
+                .ok_or(ErrorCode::AttestationRequired)?
+                .verify(
+                    ctx.accounts.agreement.tenant_id,
+                    &ctx.accounts.investor.wallet,
+                    ctx.accounts.clock.unix_timestamp,
+                )?;
+        }
+        // The escrow of a terminated deal holds the investor's refund, so tokens wait for the
+        // wallet to be unblocked
+        if terminated && BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+            return Err(ErrorCode::WalletBlocked.into());
         }
-
         // Calculate commission (1% of tokens)
-        let investor_tokens = ctx.accounts.investor.token_allocation;
+        let investor_tokens = ctx
+            .accounts
+            .agreement
+            .claimable_tokens(ctx.accounts.investor.token_allocation);
+        if terminated {
+            if investor_tokens == 0 {
+                return Err(ErrorCode::NoFundsToWithdraw.into());
+            }
+            ctx.accounts.agreement.tokens_owed = ctx
+                .accounts
+                .agreement
+                .tokens_owed
+                .checked_sub(investor_tokens)
+                .ok_or(ErrorCode::Overflow)?;
+        }
         let commission_amount =
             (investor_tokens * ctx.accounts.settings.commission_basis_points as u64) / BASIC_POINTS;
         let final_token_amount = investor_tokens - commission_amount;
 
-        // Transfer commission to owner if needed
-        if commission_amount > 0 {
+        // The referral fee is carved out of the commission
+        let referral_fee = pay_referral_fee(
+            &ctx.accounts.agreement,
+            &ctx.accounts.project_token_account,
+            &ctx.accounts.investor,
+            ctx.accounts
+                .referrer_token_account
+                .as_ref()
+                .map(|account| account.as_ref()),
+            ctx.accounts
+                .referrer_blocked_wallet
+                .as_ref()
+                .map(|account| account.as_ref()),
+            &ctx.accounts.token_program.to_account_info(),
+            commission_amount,
+        )?;
+        ctx.accounts.agreement.referral_fees_paid += referral_fee;
+
+        // Transfer the rest of the commission to the fee recipients if needed
+        let platform_fee = commission_amount - referral_fee;
+        if platform_fee > 0 {
+            let fees = pay_commission(
+                &ctx.accounts.agreement,
+                &ctx.accounts.project_token_account,
+                ctx.accounts.settings.treasury(),
+                ctx.accounts.owner_token_account.as_ref(),
+                ctx.remaining_accounts,
+                &ctx.accounts.token_program.to_account_info(),
+                platform_fee,
+            )?;
+            for (share, amount) in ctx.accounts.agreement.fee_split.iter_mut().zip(fees) {
+                share.tokens_collected += amount;
+            }
+        }
+        ctx.accounts.agreement.token_commision_collected += commission_amount;
+
+        if BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+            ctx.accounts.investor.escrowed_amount = final_token_amount;
+            emit!(PayoutEscrowed {
+                agreement: ctx.accounts.agreement.key(),
+                wallet: ctx.accounts.investor.wallet,
+                amount: final_token_amount,
+            });
+        } else {
+            // Transfer tokens to investor
             anchor_spl::token::transfer(
                 CpiContext::new_with_signer(
                     ctx.accounts.token_program.to_account_info(),
                     Transfer {
                         from: ctx.accounts.project_token_account.to_account_info(),
-                        to: ctx.accounts.owner_token_account.to_account_info(),
+                        to: ctx.accounts.investor_token_account.to_account_info(),
                         authority: ctx.accounts.agreement.to_account_info(),
                     },
                     &[&[
                         AGREEMENT_SEED.as_bytes(),
+                        ctx.accounts.agreement.tenant_seed().as_ref(),
                         ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
                         &[ctx.accounts.agreement.bump],
                     ]],
                 ),
-                commission_amount,
+                final_token_amount,
             )?;
-
-            ctx.accounts.agreement.token_commision_collected += commission_amount;
         }
 
-        // Transfer tokens to investor
-        anchor_spl::token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.project_token_account.to_account_info(),
-                    to: ctx.accounts.investor_token_account.to_account_info(),
-                    authority: ctx.accounts.agreement.to_account_info(),
-                },
-                &[&[
-                    AGREEMENT_SEED.as_bytes(),
-                    ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
-                    &[ctx.accounts.agreement.bump],
-                ]],
-            ),
-            final_token_amount,
-        )?;
-
         ctx.accounts.investor.has_withdrawn_tokens = true;
 
         Ok(())
@@ -2,30 +2,34 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount, Transfer};
 
 use crate::{
-    error::ErrorCode, Agreement, Investor, Settings, AGREEMENT_SEED, BASIC_POINTS, PROJECT_SEED,
-    SETTINGS_SEED,
+    error::ErrorCode,
+    utils::{pay_commission, pay_referral_fee},
+    Agreement, Attestation, BlockedWallet, Investor, PayoutEscrowed, Settings, AGREEMENT_SEED,
+    BASIC_POINTS, PROJECT_SEED, SETTINGS_SEED,
 };
 
 #[derive(Accounts)]
 #[instruction(params: WithdrawTokensBatchParams)]
 pub struct WithdrawTokensBatch<'info> {
     #[account(
-        seeds = [SETTINGS_SEED.as_bytes()],
+        seeds = [SETTINGS_SEED.as_bytes(), agreement.tenant_seed().as_ref()],
         bump
     )]
     pub settings: Account<'info, Settings>,
     #[account(mut,
-        seeds = [AGREEMENT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agreement: Account<'info, Agreement>,
     #[account(mut,
-        seeds = [PROJECT_SEED.as_bytes(), agreement.agreement_id.to_le_bytes().as_ref()],
+        seeds = [PROJECT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
         bump,
     )]
     pub project_token_account: Account<'info, TokenAccount>,
+    /// Treasury token account, `None` when the agreement splits its commission and no fee
+    /// recipient is blocked.
     #[account(mut)]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -44,64 +48,144 @@ impl<'info> WithdrawTokensBatch<'info> {
         if !ctx.accounts.agreement.seller_deposited {
             return Err(ErrorCode::TokensNotDeposited.into());
         }
-        if ctx.accounts.agreement.agreement_cancelled {
+        let terminated = ctx.accounts.agreement.terminated;
+        if ctx.accounts.agreement.agreement_cancelled && !terminated {
             return Err(ErrorCode::AgreementAlreadyCancelled.into());
         }
         if ctx.accounts.agreement.hold_duration_start == 0 {
             return Err(ErrorCode::HoldDurationNotStarted.into());
         }
-        if ctx.accounts.clock.unix_timestamp
-            < ctx.accounts.agreement.hold_duration_start + ctx.accounts.agreement.hold_duration
+        if !terminated
+            && ctx.accounts.clock.unix_timestamp
+                < ctx.accounts.agreement.hold_duration_start + ctx.accounts.agreement.hold_duration
         {
             return Err(ErrorCode::HoldDurationPeriodNotExpired.into());
         }
-        if ctx.accounts.settings.owner != ctx.accounts.owner_token_account.owner {
-            return Err(ErrorCode::InvalidDestination.into());
-        }
-
         let mut success_count = 0;
         let mut total_commission = 0;
-
-        for accounts in ctx.remaining_accounts.chunks(2) {
+        let mut platform_commission = 0;
+
+        // Fee recipient token accounts and blocklist PDAs come first, in `fee_split` order
+        let fee_accounts_count =
+            (ctx.accounts.agreement.fee_split.len() * 2).min(ctx.remaining_accounts.len());
+        let (fee_accounts, investor_accounts) = ctx.remaining_accounts.split_at(fee_accounts_count);
+
+        // Each investor comes with its token account and blocklist PDA, plus its attestation
+        // when the agreement requires KYC. Referred investors are followed by the referrer token
+        // account and blocklist PDA.
+        let chunk_size = 3 + ctx.accounts.agreement.compliance_required as usize;
+        let mut unprocessed_accounts = investor_accounts;
+        while !unprocessed_accounts.is_empty() {
+            if unprocessed_accounts.len() < chunk_size {
+                return Err(ErrorCode::InvalidInvestorAccount.into());
+            }
+            let (accounts, rest) = unprocessed_accounts.split_at(chunk_size);
+            unprocessed_accounts = rest;
             let investor_account = &accounts[0];
             let investor_token_account = &accounts[1];
 
             // decode investor account
-            let mut investor = Investor::try_from_slice(&investor_account.data.borrow())?;
+            let mut investor = Account::<Investor>::try_from(investor_account)?;
             if investor.agreement != ctx.accounts.agreement.key() {
                 return Err(ErrorCode::InvalidInvestorAccount.into());
             }
-
-            if !investor.investor_deposited || investor.has_withdrawn_tokens {
+            let referral_accounts = if investor.referrer != Pubkey::default() {
+                if unprocessed_accounts.len() < 2 {
+                    return Err(ErrorCode::InvalidReferrerAccount.into());
+                }
+                let (referral_accounts, rest) = unprocessed_accounts.split_at(2);
+                unprocessed_accounts = rest;
+                referral_accounts
+            } else {
+                &[]
+            };
+
+            if (!investor.investor_deposited && !investor.refunded) || investor.has_withdrawn_tokens
+            {
                 continue;
             }
+            if ctx.accounts.agreement.compliance_required {
+                Account::<Attestation>::try_from(&accounts[3])?.verify(
+                    ctx.accounts.agreement.tenant_id,
+                    &investor.wallet,
+                    ctx.accounts.clock.unix_timestamp,
+                )?;
+            }
 
-            let investor_tokens = investor.token_allocation;
-            let commission_amount = (investor_tokens
+            let investor_tokens = ctx
+                .accounts
+                .agreement
+                .claimable_tokens(investor.token_allocation);
+            let blocked = BlockedWallet::is_blocked(
+                &accounts[2],
+                &ctx.accounts.agreement.tenant_seed(),
+                &investor.wallet,
+            )?;
+            // Blocked investors of a terminated deal keep their tokens until unblocked, see
+            // `withdraw_tokens`
+            if terminated {
+                if investor_tokens == 0 || blocked {
+                    continue;
+                }
+                ctx.accounts.agreement.tokens_owed = ctx
+                    .accounts
+                    .agreement
+                    .tokens_owed
+                    .checked_sub(investor_tokens)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+            let commission_amount = investor_tokens
                 * ctx.accounts.settings.commission_basis_points as u64
-                / BASIC_POINTS) as u64;
+                / BASIC_POINTS;
             let final_token_amount = investor_tokens - commission_amount;
 
-            total_commission += commission_amount;
-
-            anchor_spl::token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.project_token_account.to_account_info(),
-                        to: investor_token_account.to_account_info(),
-                        authority: ctx.accounts.agreement.to_account_info(),
-                    },
-                    &[&[
-                        AGREEMENT_SEED.as_bytes(),
-                        ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
-                        &[ctx.accounts.agreement.bump],
-                    ]],
-                ),
-                final_token_amount,
+            let referral_fee = pay_referral_fee(
+                &ctx.accounts.agreement,
+                &ctx.accounts.project_token_account,
+                &investor,
+                referral_accounts.first(),
+                referral_accounts.get(1),
+                &ctx.accounts.token_program.to_account_info(),
+                commission_amount,
             )?;
+            ctx.accounts.agreement.referral_fees_paid += referral_fee;
+            total_commission += commission_amount;
+            platform_commission += commission_amount - referral_fee;
+
+            if blocked {
+                investor.escrowed_amount = final_token_amount;
+                emit!(PayoutEscrowed {
+                    agreement: ctx.accounts.agreement.key(),
+                    wallet: investor.wallet,
+                    amount: final_token_amount,
+                });
+            } else {
+                if Account::<TokenAccount>::try_from(investor_token_account)?.owner
+                    != investor.wallet
+                {
+                    return Err(ErrorCode::InvalidDestination.into());
+                }
+                anchor_spl::token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.project_token_account.to_account_info(),
+                            to: investor_token_account.to_account_info(),
+                            authority: ctx.accounts.agreement.to_account_info(),
+                        },
+                        &[&[
+                            AGREEMENT_SEED.as_bytes(),
+                            ctx.accounts.agreement.tenant_seed().as_ref(),
+                            ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
+                            &[ctx.accounts.agreement.bump],
+                        ]],
+                    ),
+                    final_token_amount,
+                )?;
+            }
 
             investor.has_withdrawn_tokens = true;
+            investor.exit(ctx.program_id)?;
             success_count += 1;
         }
 
@@ -109,26 +193,21 @@ impl<'info> WithdrawTokensBatch<'info> {
             return Err(ErrorCode::NotAnInvestor.into());
         }
 
-        if total_commission > 0 {
-            anchor_spl::token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.project_token_account.to_account_info(),
-                        to: ctx.accounts.owner_token_account.to_account_info(),
-                        authority: ctx.accounts.agreement.to_account_info(),
-                    },
-                    &[&[
-                        AGREEMENT_SEED.as_bytes(),
-                        ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
-                        &[ctx.accounts.agreement.bump],
-                    ]],
-                ),
-                total_commission,
+        if platform_commission > 0 {
+            let fees = pay_commission(
+                &ctx.accounts.agreement,
+                &ctx.accounts.project_token_account,
+                ctx.accounts.settings.treasury(),
+                ctx.accounts.owner_token_account.as_ref(),
+                fee_accounts,
+                &ctx.accounts.token_program.to_account_info(),
+                platform_commission,
             )?;
-
-            ctx.accounts.agreement.token_commision_collected += total_commission;
+            for (share, amount) in ctx.accounts.agreement.fee_split.iter_mut().zip(fees) {
+                share.tokens_collected += amount;
+            }
         }
+        ctx.accounts.agreement.token_commision_collected += total_commission;
 
         Ok(())
     }
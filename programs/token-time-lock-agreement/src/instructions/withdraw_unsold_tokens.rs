@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+use crate::{
+    error::ErrorCode, Agreement, BlockedWallet, AGREEMENT_SEED, BLOCKLIST_SEED, PROJECT_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(params: WithdrawUnsoldTokensParams)]
+pub struct WithdrawUnsoldTokens<'info> {
+    #[account(mut,
+        seeds = [AGREEMENT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agreement: Account<'info, Agreement>,
+    #[account(mut,
+        seeds = [PROJECT_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.agreement_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub project_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Blocklist PDA of the seller, unsold tokens stay in the vault while blocked.
+    #[account(
+        seeds = [BLOCKLIST_SEED.as_bytes(), agreement.tenant_seed().as_ref(), agreement.token_seller.as_ref()],
+        bump,
+    )]
+    pub blocked_wallet: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct WithdrawUnsoldTokensParams;
+
+impl<'info> WithdrawUnsoldTokens<'info> {
+    pub fn handle(
+        ctx: &mut Context<'_, '_, 'info, 'info, Self>,
+        _: WithdrawUnsoldTokensParams,
+    ) -> Result<()> {
+        if ctx.accounts.agreement.token_seller != *ctx.accounts.payer.key {
+            return Err(ErrorCode::OnlySellerAllowed.into());
+        }
+        if ctx.accounts.agreement.agreement_cancelled {
+            return Err(ErrorCode::AgreementAlreadyCancelled.into());
+        }
+        // Public sales leave the tokens rounded off each purchase unsold
+        if !ctx.accounts.agreement.partial_fill() && ctx.accounts.agreement.token_price == 0 {
+            return Err(ErrorCode::InvalidSoftCap.into());
+        }
+        if ctx.accounts.agreement.hold_duration_start == 0 {
+            return Err(ErrorCode::HoldDurationNotStarted.into());
+        }
+        if BlockedWallet::exists(&ctx.accounts.blocked_wallet) {
+            return Err(ErrorCode::WalletBlocked.into());
+        }
+        if ctx.accounts.seller_token_account.owner != ctx.accounts.agreement.token_seller {
+            return Err(ErrorCode::InvalidDestination.into());
+        }
+
+        let unsold_tokens = ctx
+            .accounts
+            .agreement
+            .expected_tokens
+            .checked_sub(ctx.accounts.agreement.tokens_sold)
+            .ok_or(ErrorCode::Overflow)?;
+        if ctx.accounts.agreement.unsold_tokens_withdrawn || unsold_tokens == 0 {
+            return Err(ErrorCode::NoFundsToWithdraw.into());
+        }
+
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.project_token_account.to_account_info(),
+                    to: ctx.accounts.seller_token_account.to_account_info(),
+                    authority: ctx.accounts.agreement.to_account_info(),
+                },
+                &[&[
+                    AGREEMENT_SEED.as_bytes(),
+                    ctx.accounts.agreement.tenant_seed().as_ref(),
+                    ctx.accounts.agreement.agreement_id.to_le_bytes().as_ref(),
+                    &[ctx.accounts.agreement.bump],
+                ]],
+            ),
+            unsold_tokens,
+        )?;
+
+        ctx.accounts.agreement.unsold_tokens_withdrawn = true;
+
+        Ok(())
+    }
+}
@@ -1,12 +1,15 @@
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
+pub mod utils;
 
 use anchor_lang::prelude::*;
 use solana_security_txt::security_txt;
 
 pub use constants::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -37,13 +40,83 @@ pub mod token_time_lock_agreement {
         Initialize::handle(&mut ctx, params)
     }
 
+    pub fn update_settings<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, UpdateSettings<'info>>,
+        params: UpdateSettingsParams,
+    ) -> Result<()> {
+        UpdateSettings::handle(&mut ctx, params)
+    }
+
+    pub fn grant_role<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, GrantRole<'info>>,
+        params: GrantRoleParams,
+    ) -> Result<()> {
+        GrantRole::handle(&mut ctx, params)
+    }
+
+    pub fn revoke_role<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, RevokeRole<'info>>,
+        params: RevokeRoleParams,
+    ) -> Result<()> {
+        RevokeRole::handle(&mut ctx, params)
+    }
+
+    pub fn create_proposal<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, CreateProposal<'info>>,
+        params: CreateProposalParams,
+    ) -> Result<()> {
+        CreateProposal::handle(&mut ctx, params)
+    }
+
+    pub fn approve_proposal<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, ApproveProposal<'info>>,
+        params: ApproveProposalParams,
+    ) -> Result<()> {
+        ApproveProposal::handle(&mut ctx, params)
+    }
+
+    pub fn block_wallet<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, BlockWallet<'info>>,
+        params: BlockWalletParams,
+    ) -> Result<()> {
+        BlockWallet::handle(&mut ctx, params)
+    }
+
+    pub fn unblock_wallet<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, UnblockWallet<'info>>,
+        params: UnblockWalletParams,
+    ) -> Result<()> {
+        UnblockWallet::handle(&mut ctx, params)
+    }
+
+    pub fn issue_attestation<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, IssueAttestation<'info>>,
+        params: IssueAttestationParams,
+    ) -> Result<()> {
+        IssueAttestation::handle(&mut ctx, params)
+    }
+
+    pub fn revoke_attestation<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, RevokeAttestation<'info>>,
+        params: RevokeAttestationParams,
+    ) -> Result<()> {
+        RevokeAttestation::handle(&mut ctx, params)
+    }
+
     pub fn initialize_agreement<'info>(
         mut ctx: Context<'_, '_, 'info, 'info, InitializeAgreement<'info>>,
         params: InitializeAgreementParams,
-    ) -> Result<()> {
+    ) -> Result<u64> {
         InitializeAgreement::handle(&mut ctx, params)
     }
 
+    pub fn accept_agreement<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, AcceptAgreement<'info>>,
+        params: AcceptAgreementParams,
+    ) -> Result<()> {
+        AcceptAgreement::handle(&mut ctx, params)
+    }
+
     pub fn add_investors<'info>(
         mut ctx: Context<'_, '_, 'info, 'info, AddInvestors<'info>>,
         params: AddInvestorsParams,
@@ -51,6 +124,48 @@ pub mod token_time_lock_agreement {
         AddInvestors::handle(&mut ctx, params)
     }
 
+    pub fn add_payment_mint<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, AddPaymentMint<'info>>,
+        params: AddPaymentMintParams,
+    ) -> Result<()> {
+        AddPaymentMint::handle(&mut ctx, params)
+    }
+
+    pub fn set_milestones<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, SetMilestones<'info>>,
+        params: SetMilestonesParams,
+    ) -> Result<()> {
+        SetMilestones::handle(&mut ctx, params)
+    }
+
+    pub fn add_to_waitlist<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, AddToWaitlist<'info>>,
+        params: AddToWaitlistParams,
+    ) -> Result<()> {
+        AddToWaitlist::handle(&mut ctx, params)
+    }
+
+    pub fn expire_allocation<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, ExpireAllocation<'info>>,
+        params: ExpireAllocationParams,
+    ) -> Result<()> {
+        ExpireAllocation::handle(&mut ctx, params)
+    }
+
+    pub fn skip_waitlist_entry<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, SkipWaitlistEntry<'info>>,
+        params: SkipWaitlistEntryParams,
+    ) -> Result<()> {
+        SkipWaitlistEntry::handle(&mut ctx, params)
+    }
+
+    pub fn acknowledge_document<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, AcknowledgeDocument<'info>>,
+        params: AcknowledgeDocumentParams,
+    ) -> Result<()> {
+        AcknowledgeDocument::handle(&mut ctx, params)
+    }
+
     pub fn deposit_stablecoins<'info>(
         mut ctx: Context<'_, '_, 'info, 'info, DepositStablecoins<'info>>,
         params: DepositStablecoinsParams,
@@ -58,6 +173,34 @@ pub mod token_time_lock_agreement {
         DepositStablecoins::handle(&mut ctx, params)
     }
 
+    pub fn purchase_tokens<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, PurchaseTokens<'info>>,
+        params: PurchaseTokensParams,
+    ) -> Result<()> {
+        PurchaseTokens::handle(&mut ctx, params)
+    }
+
+    pub fn commit_funds<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, CommitFunds<'info>>,
+        params: CommitFundsParams,
+    ) -> Result<()> {
+        CommitFunds::handle(&mut ctx, params)
+    }
+
+    pub fn investor_withdraw_commitment<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, InvestorWithdrawCommitment<'info>>,
+        params: InvestorWithdrawCommitmentParams,
+    ) -> Result<()> {
+        InvestorWithdrawCommitment::handle(&mut ctx, params)
+    }
+
+    pub fn deposit_project_tokens<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, DepositProjectTokens<'info>>,
+        params: DepositProjectTokensParams,
+    ) -> Result<()> {
+        DepositProjectTokens::handle(&mut ctx, params)
+    }
+
     pub fn process_token_deposit<'info>(
         mut ctx: Context<'_, '_, 'info, 'info, ProcessTokenDeposit<'info>>,
         params: ProcessTokenDepositParams,
@@ -65,6 +208,34 @@ pub mod token_time_lock_agreement {
         ProcessTokenDeposit::handle(&mut ctx, params)
     }
 
+    pub fn slash_seller_bond<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, SlashSellerBond<'info>>,
+        params: SlashSellerBondParams,
+    ) -> Result<()> {
+        SlashSellerBond::handle(&mut ctx, params)
+    }
+
+    pub fn claim_bond_share<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, ClaimBondShare<'info>>,
+        params: ClaimBondShareParams,
+    ) -> Result<()> {
+        ClaimBondShare::handle(&mut ctx, params)
+    }
+
+    pub fn sweep_bond_shares<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, SweepBondShares<'info>>,
+        params: SweepBondSharesParams,
+    ) -> Result<()> {
+        SweepBondShares::handle(&mut ctx, params)
+    }
+
+    pub fn reclaim_seller_bond<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, ReclaimSellerBond<'info>>,
+        params: ReclaimSellerBondParams,
+    ) -> Result<()> {
+        ReclaimSellerBond::handle(&mut ctx, params)
+    }
+
     pub fn withdraw_excess_tokens<'info>(
         mut ctx: Context<'_, '_, 'info, 'info, WithdrawExcessTokens<'info>>,
         params: WithdrawExcessTokensParams,
@@ -72,6 +243,27 @@ pub mod token_time_lock_agreement {
         WithdrawExcessTokens::handle(&mut ctx, params)
     }
 
+    pub fn settle_commitments<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, SettleCommitments<'info>>,
+        params: SettleCommitmentsParams,
+    ) -> Result<()> {
+        SettleCommitments::handle(&mut ctx, params)
+    }
+
+    pub fn finalize_funding<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, FinalizeFunding<'info>>,
+        params: FinalizeFundingParams,
+    ) -> Result<()> {
+        FinalizeFunding::handle(&mut ctx, params)
+    }
+
+    pub fn approve_milestone<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, ApproveMilestone<'info>>,
+        params: ApproveMilestoneParams,
+    ) -> Result<()> {
+        ApproveMilestone::handle(&mut ctx, params)
+    }
+
     pub fn withdraw_funds<'info>(
         mut ctx: Context<'_, '_, 'info, 'info, WithdrawFunds<'info>>,
         params: WithdrawFundsParams,
@@ -93,6 +285,13 @@ pub mod token_time_lock_agreement {
         WithdrawTokensBatch::handle(&mut ctx, params)
     }
 
+    pub fn withdraw_unsold_tokens<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, WithdrawUnsoldTokens<'info>>,
+        params: WithdrawUnsoldTokensParams,
+    ) -> Result<()> {
+        WithdrawUnsoldTokens::handle(&mut ctx, params)
+    }
+
     pub fn cancel_agreement<'info>(
         mut ctx: Context<'_, '_, 'info, 'info, CancelAgreement<'info>>,
         params: CancelAgreementParams,
@@ -113,4 +312,39 @@ pub mod token_time_lock_agreement {
     ) -> Result<()> {
         WithdrawCancelledFundsBatch::handle(&mut ctx, params)
     }
+
+    pub fn release_escrow<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, ReleaseEscrow<'info>>,
+        params: ReleaseEscrowParams,
+    ) -> Result<()> {
+        ReleaseEscrow::handle(&mut ctx, params)
+    }
+
+    pub fn release_revenue_share<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, ReleaseRevenueShare<'info>>,
+        params: ReleaseRevenueShareParams,
+    ) -> Result<()> {
+        ReleaseRevenueShare::handle(&mut ctx, params)
+    }
+
+    pub fn migrate_settings<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, MigrateSettings<'info>>,
+        params: MigrateSettingsParams,
+    ) -> Result<()> {
+        MigrateSettings::handle(&mut ctx, params)
+    }
+
+    pub fn migrate_agreement<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, MigrateAgreement<'info>>,
+        params: MigrateAgreementParams,
+    ) -> Result<()> {
+        MigrateAgreement::handle(&mut ctx, params)
+    }
+
+    pub fn migrate_investor<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, MigrateInvestor<'info>>,
+        params: MigrateInvestorParams,
+    ) -> Result<()> {
+        MigrateInvestor::handle(&mut ctx, params)
+    }
 }
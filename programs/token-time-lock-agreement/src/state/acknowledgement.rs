@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+/// An investor's signature over the legal document of an agreement.
+#[account]
+#[derive(InitSpace)]
+pub struct Acknowledgement {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub document_hash: [u8; 32],
+    pub acknowledged_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+impl Acknowledgement {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + Acknowledgement::INIT_SPACE;
+}
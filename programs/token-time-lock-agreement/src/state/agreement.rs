@@ -1,7 +1,12 @@
 use anchor_lang::prelude::*;
 
+use crate::{
+    tenant_seed, Investor, BASIC_POINTS, MAX_DOCUMENT_URI_LEN, MAX_FEE_SHARES, MAX_REVENUE_SHARES,
+    PAYMENT_SEED, PROJECT_SEED,
+};
+
 #[account]
-#[derive(Default)]
+#[derive(InitSpace)]
 pub struct Agreement {
     pub agreement_id: u64,
     pub payment_token_mint: Pubkey,
@@ -23,4 +28,185 @@ pub struct Agreement {
     pub investors_count: u64,
     pub owner: Pubkey,
     pub bump: u8,
+    pub version: u8,
+    pub tenant_id: u64,
+    pub token_depositor: Pubkey, // signer of `deposit_project_tokens`, unset for balance-based deposits
+    pub deposited_tokens: u64,
+    pub document_hash: [u8; 32],
+    pub acceptance_required: bool, // false for agreements created before countersigning
+    pub company_acceptance_required: bool,
+    pub seller_accepted: bool,
+    pub company_accepted: bool,
+    pub compliance_required: bool, // deposits and claims need a valid KYC attestation
+    pub seller_escrowed_amount: u64, // seller payout held in the vault while the seller is blocked
+    pub deposit_window: i64, // seconds an investor has to deposit after allocation, 0 disables
+    pub waitlist_head: u64,  // position of the next waitlisted wallet to serve
+    pub waitlist_tail: u64,  // position the next waitlisted wallet is added at
+    pub soft_cap: u64, // minimum raise by `funding_deadline`, 0 requires exactly `expected_payment`
+    pub funding_deadline: i64,
+    pub tokens_sold: u64, // token allocations of investors who deposited
+    pub unsold_tokens_withdrawn: bool,
+    pub token_price: u64, // payment units per `PRICE_SCALE` token units, 0 disables public sale
+    pub min_ticket: u64,
+    pub max_ticket: u64,      // 0 for no upper bound
+    pub wallet_cap: u64,      // total payment per wallet across purchases, 0 for no cap
+    pub commit_deadline: i64, // end of the commit window for pro rata rounds, 0 disables
+    pub total_committed: u64, // commitments before settlement, may exceed `expected_payment`
+    pub settled_count: u64,
+    pub direct_payments_total: u64, // paid straight to the company wallet, see `PaymentReceipt`
+    pub payment_receipts_count: u64,
+    pub secondary_invested: u64, // part of `total_invested` paid in other `PaymentMint`s
+    pub seller_bond: u64,        // posted by the seller on acceptance, 0 disables
+    pub delivery_deadline: i64,  // tokens processed after this forfeit the bond
+    pub bond_to_investors: bool, // slashed bond goes pro rata to funded investors instead of the company
+    pub bond_deposited: bool,
+    pub bond_returned: bool,
+    pub bond_slashed: bool,
+    pub bond_slash_base: u64, // `total_invested` when the bond was slashed to investors
+    pub milestones_count: u8, // proceeds are released per `MilestonePlan`, 0 releases them in full
+    pub approved_bps: u16,    // share of the proceeds approved for release
+    pub funds_released: u64,  // `payment_token_mint` proceeds paid out, commission included
+    pub payment_mints_count: u8,
+    pub seller_escrowed_tokens: u64, // project tokens held in the vault while the seller is blocked
+    pub terminated: bool, // milestone deal ended early, investors claim the released share of tokens
+    pub tokens_owed: u64, // released share of `tokens_sold` not yet claimed after termination
+    pub cancelled_at: i64, // when `agreement_cancelled` was set
+    pub bond_claim_window: i64, // seconds investors have to claim a bond slashed to them
+    pub bond_slashed_at: i64,
+    pub referral_fees_paid: u64, // project tokens paid to referrers out of the commission
+    pub funds_commission_paid: u64, // `payment_token_mint` commission paid out
+    // Fixed-size fields are added from here, variable-length ones stay at the end.
+    pub reserved: [u8; 64],
+    #[max_len(MAX_DOCUMENT_URI_LEN)]
+    pub document_uri: String, // where the document behind `document_hash` is published
+    #[max_len(MAX_REVENUE_SHARES)]
+    pub revenue_split: Vec<RevenueShare>, // recipients of the seller proceeds, empty pays the seller
+    #[max_len(MAX_FEE_SHARES)]
+    pub fee_split: Vec<FeeRecipient>, // commission recipients, empty pays the treasury
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct RevenueShare {
+    pub recipient: Pubkey,
+    pub basis_points: u16, // share of the seller proceeds
+    pub paid: u64,         // `payment_token_mint` units paid out so far
+    pub escrowed: u64, // `payment_token_mint` units held in the vault while the recipient is blocked
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct FeeRecipient {
+    pub recipient: Pubkey,
+    pub basis_points: u16,     // share of the commission
+    pub funds_collected: u64,  // `payment_token_mint` commission received
+    pub tokens_collected: u64, // project token commission received
+}
+
+impl Agreement {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + Agreement::INIT_SPACE;
+    /// Discriminator and fields of agreements created before versioning, which end where
+    /// `version` starts.
+    pub const LEGACY_LEN: usize = 8 + 252;
+
+    pub fn tenant_seed(&self) -> Vec<u8> {
+        tenant_seed(self.tenant_id)
+    }
+
+    /// Whether funding can complete below `expected_payment`, for soft cap and pro rata rounds.
+    pub fn partial_fill(&self) -> bool {
+        self.soft_cap > 0 || self.commit_deadline > 0
+    }
+
+    /// Amount raised once funding completed, below `expected_payment` only for partial fills.
+    pub fn raised_amount(&self) -> Option<u64> {
+        if self.partial_fill() {
+            (self.hold_duration_start != 0).then_some(self.total_invested)
+        } else {
+            (self.total_invested == self.expected_payment).then_some(self.expected_payment)
+        }
+    }
+
+    /// Vault holding an investor's escrowed payout, or the seller's when `investor` is `None`.
+    /// Escrowed seller proceeds are released before escrowed project tokens.
+    pub fn escrow_vault_seed(&self, investor: Option<&Investor>) -> &'static str {
+        let payment = match investor {
+            Some(investor) => investor.payment_escrowed(),
+            None => self.seller_escrowed_amount > 0,
+        };
+        if payment {
+            PAYMENT_SEED
+        } else {
+            PROJECT_SEED
+        }
+    }
+
+    /// Whether any wallet can buy in at `token_price` through `purchase_tokens`.
+    pub fn public_sale(&self) -> bool {
+        self.token_price > 0
+    }
+
+    /// Share of the proceeds the seller can withdraw, in basis points.
+    pub fn released_bps(&self) -> u64 {
+        if self.milestones_count == 0 {
+            BASIC_POINTS
+        } else {
+            self.approved_bps as u64
+        }
+    }
+
+    /// Tokens an investor with `allocation` claims, only the released share once terminated.
+    pub fn claimable_tokens(&self, allocation: u64) -> u64 {
+        if self.terminated {
+            (allocation as u128 * self.approved_bps as u128 / BASIC_POINTS as u128) as u64
+        } else {
+            allocation
+        }
+    }
+
+    /// Part of an investor's `amount` refunded on cancellation, less proceeds already approved
+    /// for release.
+    pub fn refundable(&self, amount: u64) -> u64 {
+        (amount as u128 * (BASIC_POINTS - self.approved_bps as u64) as u128 / BASIC_POINTS as u128)
+            as u64
+    }
+
+    /// Whether delivering now returns the seller bond.
+    pub fn bond_returnable(&self, now: i64) -> bool {
+        self.bond_deposited
+            && !self.bond_returned
+            && !self.bond_slashed
+            && now <= self.delivery_deadline
+    }
+
+    /// Whether the agreement was called off before the seller was late, which releases the bond
+    /// back to the seller instead of forfeiting it.
+    pub fn cancelled_before_delivery_deadline(&self) -> bool {
+        self.agreement_cancelled && self.cancelled_at <= self.delivery_deadline
+    }
+
+    /// Whether the seller missed `delivery_deadline` on a live agreement and forfeits the bond.
+    pub fn bond_slashable(&self, now: i64) -> bool {
+        self.bond_deposited
+            && !self.bond_returned
+            && !self.bond_slashed
+            && now > self.delivery_deadline
+            && !self.cancelled_before_delivery_deadline()
+    }
+
+    pub fn cancel(&mut self, now: i64) {
+        self.agreement_cancelled = true;
+        self.cancelled_at = now;
+    }
+
+    /// Whether any party countersigned the terms, which can no longer change from then on.
+    pub fn terms_signed(&self) -> bool {
+        self.seller_accepted || self.company_accepted
+    }
+
+    /// Whether every party required to countersign the terms has done so.
+    pub fn accepted(&self) -> bool {
+        !self.acceptance_required
+            || (self.seller_accepted
+                && (!self.company_acceptance_required || self.company_accepted))
+    }
 }
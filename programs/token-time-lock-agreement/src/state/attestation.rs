@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+/// KYC status of a wallet within a tenant, issued by a compliance role holder.
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation {
+    pub tenant_id: u64,
+    pub wallet: Pubkey,
+    pub issuer: Pubkey,
+    pub jurisdiction: u16, // ISO 3166-1 numeric country code
+    pub issued_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+impl Attestation {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + Attestation::INIT_SPACE;
+
+    pub fn verify(&self, tenant_id: u64, wallet: &Pubkey, now: i64) -> Result<()> {
+        if self.tenant_id != tenant_id || self.wallet != *wallet {
+            return Err(ErrorCode::InvalidAttestation.into());
+        }
+        if now >= self.expires_at {
+            return Err(ErrorCode::AttestationExpired.into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, BLOCKLIST_SEED};
+
+/// Blocklist entry of a wallet within a tenant, the wallet is blocked while the account exists.
+#[account]
+#[derive(InitSpace)]
+pub struct BlockedWallet {
+    pub tenant_id: u64,
+    pub wallet: Pubkey,
+    pub blocked_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+impl BlockedWallet {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + BlockedWallet::INIT_SPACE;
+
+    /// Whether `entry`, already checked to be the blocklist PDA of a wallet, holds a live entry.
+    pub fn exists(entry: &AccountInfo) -> bool {
+        entry.owner == &crate::ID && !entry.data_is_empty()
+    }
+
+    /// Checks `entry` is the blocklist PDA of `wallet` and returns whether the wallet is blocked.
+    pub fn is_blocked(entry: &AccountInfo, tenant_seed: &[u8], wallet: &Pubkey) -> Result<bool> {
+        let (address, _) = Pubkey::find_program_address(
+            &[BLOCKLIST_SEED.as_bytes(), tenant_seed, wallet.as_ref()],
+            &crate::ID,
+        );
+        if address != *entry.key {
+            return Err(ErrorCode::InvalidBlocklistAccount.into());
+        }
+        Ok(Self::exists(entry))
+    }
+}
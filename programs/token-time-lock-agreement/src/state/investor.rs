@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 
 #[account]
-#[derive(Default)]
+#[derive(Default, InitSpace)]
 pub struct Investor {
     pub has_withdrawn_tokens: bool,
     pub investor_deposited: bool,
@@ -10,4 +10,66 @@ pub struct Investor {
     pub wallet: Pubkey,
     pub agreement: Pubkey,
     pub bump: u8,
+    pub version: u8,
+    pub escrowed_amount: u64, // payout held in the vault while the wallet is blocked
+    pub deposit_deadline: i64, // allocation can be expired after this, 0 never expires
+    pub settled: bool,        // pro rata share computed and the excess commitment refunded
+    pub payment_mint: Pubkey, // default for the agreement's `payment_token_mint`
+    pub paid_amount: u64,     // deposit in `payment_mint` units
+    pub paid_rate: u64,       // `PaymentMint` rate the deposit was converted at
+    pub bond_claimed: bool,   // share of a slashed seller bond paid out
+    pub referrer: Pubkey,     // default when the investor was not referred
+    pub referral_bps: u16,    // share of the token commission paid to `referrer`
+    pub refunded: bool,       // deposit refunded, `escrowed_amount` is then in `payment_mint` units
+    pub escrowed_refund: u64, // excess commitment held in the payment vault while the wallet is blocked
+    pub rent_payer: Pubkey,   // receives the rent back when the account is closed
+    pub deposited_after_slash: u64, // part of `required_amount` paid after the bond was slashed
+    pub reserved: [u8; 15],
+}
+
+impl Investor {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + Investor::INIT_SPACE;
+    /// Length of unversioned investor accounts, the version byte was their padding.
+    pub const LEGACY_LEN: usize = 8 + 83;
+
+    /// Vault seed suffix of the mint the investor paid in, empty for `payment_token_mint`.
+    pub fn payment_mint_seed(&self) -> Vec<u8> {
+        if self.payment_mint == Pubkey::default() {
+            Vec::new()
+        } else {
+            self.payment_mint.to_bytes().to_vec()
+        }
+    }
+
+    /// Whether the escrowed payout released next is held in the payment vault. An escrowed
+    /// commitment refund is released before escrowed tokens.
+    pub fn payment_escrowed(&self) -> bool {
+        self.refunded || self.escrowed_refund > 0
+    }
+
+    /// Wallet the rent is returned to on close, the agreement owner for accounts migrated from
+    /// before `rent_payer` was recorded.
+    pub fn rent_recipient(&self, agreement_owner: Pubkey) -> Pubkey {
+        if self.rent_payer == Pubkey::default() {
+            agreement_owner
+        } else {
+            self.rent_payer
+        }
+    }
+
+    /// Deposit counted in `Agreement::bond_slash_base`, which the bond share is paid on.
+    pub fn bond_share_basis(&self) -> u64 {
+        self.required_amount
+            .saturating_sub(self.deposited_after_slash)
+    }
+
+    /// Deposit returned on refund, in the units of the mint it was paid in.
+    pub fn refund_amount(&self) -> u64 {
+        if self.payment_mint == Pubkey::default() {
+            self.required_amount
+        } else {
+            self.paid_amount
+        }
+    }
 }
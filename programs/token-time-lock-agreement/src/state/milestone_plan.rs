@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::MAX_MILESTONES;
+
+/// Milestones gating the release of an agreement's proceeds to the seller.
+#[account]
+#[derive(InitSpace)]
+pub struct MilestonePlan {
+    pub agreement: Pubkey,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 32],
+    #[max_len(MAX_MILESTONES)]
+    pub milestones: Vec<Milestone>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct Milestone {
+    pub basis_points: u16, // share of the proceeds released on approval
+    pub approver: Pubkey,  // company wallet or arbiter
+    pub approved: bool,
+}
+
+impl MilestonePlan {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + MilestonePlan::INIT_SPACE;
+}
@@ -1,7 +1,29 @@
+pub mod acknowledgement;
 pub mod agreement;
+pub mod attestation;
+pub mod blocked_wallet;
 pub mod investor;
+pub mod milestone_plan;
+pub mod payment_mint;
+pub mod payment_receipt;
+pub mod price_feed;
+pub mod proposal;
+pub mod referrer;
+pub mod role;
 pub mod settings;
+pub mod waitlist_entry;
 
+pub use acknowledgement::*;
 pub use agreement::*;
+pub use attestation::*;
+pub use blocked_wallet::*;
 pub use investor::*;
+pub use milestone_plan::*;
+pub use payment_mint::*;
+pub use payment_receipt::*;
+pub use price_feed::*;
+pub use proposal::*;
+pub use referrer::*;
+pub use role::*;
 pub use settings::*;
+pub use waitlist_entry::*;
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, PriceFeed, MAX_FEE_SHARES, MAX_REVENUE_SHARES, PRICE_SCALE};
+
+/// An additional mint an agreement accepts payment in, held in its own vault.
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentMint {
+    pub agreement: Pubkey,
+    pub mint: Pubkey,
+    pub rate: u64, // `payment_token_mint` units per `PRICE_SCALE` units of this mint, 0 when oracle priced
+    pub funds_withdrawn: bool,
+    pub bump: u8,
+    pub version: u8,
+    pub oracle: Pubkey,     // `PriceFeed` account, default for the fixed `rate`
+    pub max_price_age: i64, // seconds since the feed was published
+    pub max_confidence_bps: u16, // confidence interval relative to the price
+    pub commission_paid: u64, // commission paid out in this mint
+    pub reserved: [u8; 24],
+    #[max_len(MAX_REVENUE_SHARES)]
+    pub revenue_split: Vec<RevenuePayout>, // payouts in this mint, in `Agreement::revenue_split` order
+    #[max_len(MAX_FEE_SHARES)]
+    pub fees_collected: Vec<u64>, // commission received in this mint, in `Agreement::fee_split` order
+}
+
+#[derive(Clone, Debug, Default, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct RevenuePayout {
+    pub paid: u64,
+    pub escrowed: u64, // held in the vault while the recipient is blocked
+}
+
+impl PaymentMint {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + PaymentMint::INIT_SPACE;
+
+    /// Current rate, read from `price_feed` when the mint is oracle priced.
+    pub fn current_rate(&self, price_feed: Option<&AccountInfo>, now: i64) -> Result<u64> {
+        if self.oracle == Pubkey::default() {
+            return Ok(self.rate);
+        }
+        let price_feed = price_feed.ok_or(ErrorCode::InvalidPriceFeed)?;
+        if price_feed.key() != self.oracle {
+            return Err(ErrorCode::InvalidPriceFeed.into());
+        }
+
+        PriceFeed::load(price_feed)?.checked_price(self.max_price_age, self.max_confidence_bps, now)
+    }
+
+    /// Amount of this mint that settles `amount` in `payment_token_mint` units at `rate`, rounded up.
+    pub fn mint_amount(amount: u64, rate: u64) -> u64 {
+        (amount as u128 * PRICE_SCALE as u128).div_ceil(rate as u128) as u64
+    }
+}
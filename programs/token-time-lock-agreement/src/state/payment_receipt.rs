@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::MAX_MEMO_LEN;
+
+/// A payment made straight to the company wallet outside the investor flow.
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentReceipt {
+    pub agreement: Pubkey,
+    pub receipt_id: u64,
+    pub payer: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub paid_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub nonce: u64, // picked by the payer, seeds the receipt address
+    pub reserved: [u8; 24],
+    #[max_len(MAX_MEMO_LEN)]
+    pub memo: String, // side letter reference, empty when none was given
+}
+
+impl PaymentReceipt {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + PaymentReceipt::INIT_SPACE;
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, BASIC_POINTS};
+
+/// Price account of an oracle-priced `PaymentMint`. Any program may own it as long as the
+/// data follows this little-endian layout:
+///
+/// | offset | field        | type                                                          |
+/// |--------|--------------|---------------------------------------------------------------|
+/// | 0      | header       | `[u8; 8]`, ignored, e.g. the owner program's discriminator    |
+/// | 8      | price        | `u64`, `payment_token_mint` units per `PRICE_SCALE` mint units |
+/// | 16     | confidence   | `u64`, interval around `price` in the same units              |
+/// | 24     | publish_time | `i64`, unix timestamp of the last update                      |
+pub struct PriceFeed {
+    pub price: u64,
+    pub confidence: u64,
+    pub publish_time: i64,
+}
+
+impl PriceFeed {
+    pub const LEN: usize = 32;
+
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        let data = account.try_borrow_data()?;
+        if data.len() < Self::LEN {
+            return Err(ErrorCode::InvalidPriceFeed.into());
+        }
+        let read = |offset: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&data[offset..offset + 8]);
+            bytes
+        };
+
+        Ok(Self {
+            price: u64::from_le_bytes(read(8)),
+            confidence: u64::from_le_bytes(read(16)),
+            publish_time: i64::from_le_bytes(read(24)),
+        })
+    }
+
+    /// Price after the staleness and confidence checks.
+    pub fn checked_price(&self, max_age: i64, max_confidence_bps: u16, now: i64) -> Result<u64> {
+        if self.price == 0 {
+            return Err(ErrorCode::InvalidPriceFeed.into());
+        }
+        if now - self.publish_time > max_age {
+            return Err(ErrorCode::StalePrice.into());
+        }
+        if self.confidence as u128 * BASIC_POINTS as u128
+            > self.price as u128 * max_confidence_bps as u128
+        {
+            return Err(ErrorCode::PriceConfidenceTooWide.into());
+        }
+
+        Ok(self.price)
+    }
+}
@@ -0,0 +1,124 @@
+use anchor_lang::{prelude::*, solana_program::hash::hash};
+
+use crate::{
+    error::ErrorCode, AddPaymentMintParams, BlockWalletParams, GrantRoleParams,
+    InitializeAgreementParams, RevokeRoleParams, SetMilestonesParams, Settings,
+    UnblockWalletParams, UpdateSettingsParams, MAX_MULTISIG_SIGNERS,
+};
+
+/// Admin actions that need multisig approval once `Settings::multisig_threshold` is set.
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub enum AdminAction {
+    InitializeAgreement {
+        params: Box<InitializeAgreementParams>,
+        payment_token_mint: Pubkey,
+        project_token_mint: Pubkey,
+        company_wallet: Pubkey,
+        token_seller: Pubkey,
+    },
+    UpdateSettings {
+        params: UpdateSettingsParams,
+    },
+    CancelAgreement {
+        agreement: Pubkey,
+    },
+    GrantRole {
+        params: GrantRoleParams,
+    },
+    RevokeRole {
+        params: RevokeRoleParams,
+    },
+    BlockWallet {
+        params: BlockWalletParams,
+    },
+    UnblockWallet {
+        params: UnblockWalletParams,
+    },
+    ReleaseEscrow {
+        agreement: Pubkey,
+        wallet: Pubkey,
+    },
+    AddPaymentMint {
+        agreement: Pubkey,
+        mint: Pubkey,
+        params: AddPaymentMintParams,
+    },
+    SetMilestones {
+        agreement: Pubkey,
+        params: SetMilestonesParams,
+    },
+}
+
+impl AdminAction {
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        Ok(hash(&borsh::to_vec(self)?).to_bytes())
+    }
+
+    /// Executes `proposal` for this action once multisig is enabled, otherwise only the owner may
+    /// act.
+    pub fn authorize(
+        &self,
+        settings: &Settings,
+        proposal: Option<&mut Account<Proposal>>,
+        payer: &Pubkey,
+        now: i64,
+    ) -> Result<()> {
+        if settings.multisig_enabled() {
+            proposal
+                .ok_or(ErrorCode::ProposalRequired)?
+                .execute(settings, self, now)
+        } else if settings.owner != *payer {
+            Err(ErrorCode::NotAuthorized.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub settings: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub action_hash: [u8; 32], // hash of the borsh encoded `AdminAction`
+    pub expires_at: i64,
+    pub executed: bool,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 32],
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub approvals: Vec<Pubkey>,
+}
+
+impl Proposal {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + Proposal::INIT_SPACE;
+
+    /// Marks the proposal executed if it approves `action` and enough current signers approved it.
+    pub fn execute(&mut self, settings: &Settings, action: &AdminAction, now: i64) -> Result<()> {
+        if self.executed {
+            return Err(ErrorCode::ProposalAlreadyExecuted.into());
+        }
+        if now >= self.expires_at {
+            return Err(ErrorCode::ProposalExpired.into());
+        }
+        if self.action_hash != action.hash()? {
+            return Err(ErrorCode::ProposalActionMismatch.into());
+        }
+
+        // Signers removed after approving no longer count
+        let approvals = self
+            .approvals
+            .iter()
+            .filter(|approver| settings.multisig_signers.contains(approver))
+            .count();
+        if approvals < settings.multisig_threshold as usize {
+            return Err(ErrorCode::ProposalThresholdNotMet.into());
+        }
+
+        self.executed = true;
+
+        Ok(())
+    }
+}
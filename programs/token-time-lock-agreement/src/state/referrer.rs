@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+/// Referral totals of a wallet within a tenant, registered on its first referral.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Referrer {
+    pub tenant_id: u64,
+    pub wallet: Pubkey,
+    pub referred_count: u64, // live allocations attributed to the referrer
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+impl Referrer {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + Referrer::INIT_SPACE;
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub enum RoleKind {
+    Operator,   // creates agreements and adds investors
+    Compliance, // issues investor attestations
+}
+
+impl RoleKind {
+    pub fn seed(&self) -> [u8; 1] {
+        [*self as u8]
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Role {
+    pub settings: Pubkey,
+    pub wallet: Pubkey,
+    pub kind: RoleKind,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+impl Role {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + Role::INIT_SPACE;
+}
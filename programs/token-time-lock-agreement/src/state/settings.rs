@@ -1,9 +1,78 @@
 use anchor_lang::prelude::*;
 
+use crate::{BASIC_POINTS, MAX_ALLOWED_MINTS, MAX_FEE_SHARES, MAX_MULTISIG_SIGNERS};
+
+/// Seed namespacing a tenant's PDAs. Tenant 0 is the original deployment and keeps un-namespaced addresses.
+pub fn tenant_seed(tenant_id: u64) -> Vec<u8> {
+    if tenant_id == 0 {
+        Vec::new()
+    } else {
+        tenant_id.to_le_bytes().to_vec()
+    }
+}
+
 #[account]
-#[derive(Default)]
+#[derive(InitSpace)]
 pub struct Settings {
     pub cancel_timeout: i64,          // seconds
     pub commission_basis_points: u16, // 1% (100/10000)
-    pub owner: Pubkey,                // commission receiver
+    pub owner: Pubkey,                // admin, manages roles and settings
+    pub version: u8,
+    pub next_agreement_id: u64, // assigned to the next agreement created without an explicit id
+    pub tenant_id: u64,
+    pub treasury: Pubkey,       // commission receiver, the owner when unset
+    pub multisig_threshold: u8, // approvals needed for admin actions, 0 disables multisig
+    pub next_proposal_id: u64,
+    pub reserved: [u8; 64],
+    #[max_len(MAX_ALLOWED_MINTS)]
+    pub allowed_mints: Vec<Pubkey>, // payment mints agreements may use, empty allows any
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub multisig_signers: Vec<Pubkey>,
+    #[max_len(MAX_FEE_SHARES)]
+    pub fee_split: Vec<FeeShare>, // commission recipients of new agreements, empty pays the treasury
+}
+
+impl Settings {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + Settings::INIT_SPACE;
+    /// Discriminator and fields of the unversioned layout, `version` sits right after them.
+    pub const LEGACY_LEN: usize = 8 + 8 + 2 + 32;
+
+    pub fn tenant_seed(&self) -> Vec<u8> {
+        tenant_seed(self.tenant_id)
+    }
+
+    pub fn multisig_enabled(&self) -> bool {
+        self.multisig_threshold > 0
+    }
+
+    pub fn treasury(&self) -> Pubkey {
+        if self.treasury == Pubkey::default() {
+            self.owner
+        } else {
+            self.treasury
+        }
+    }
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct FeeShare {
+    pub recipient: Pubkey,
+    pub basis_points: u16, // share of the commission
+}
+
+impl FeeShare {
+    /// Whether `split` is empty or has up to `MAX_FEE_SHARES` recipients adding up to `BASIC_POINTS`.
+    pub fn valid_split(split: &[FeeShare]) -> bool {
+        split.is_empty()
+            || (split.len() <= MAX_FEE_SHARES
+                && split
+                    .iter()
+                    .all(|share| share.basis_points > 0 && share.recipient != Pubkey::default())
+                && split
+                    .iter()
+                    .map(|share| share.basis_points as u64)
+                    .sum::<u64>()
+                    == BASIC_POINTS)
+    }
 }
@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+/// A wallet queued for allocations that expire unfunded, served in `position` order.
+#[account]
+#[derive(InitSpace)]
+pub struct WaitlistEntry {
+    pub agreement: Pubkey,
+    pub wallet: Pubkey,
+    pub position: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+impl WaitlistEntry {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + WaitlistEntry::INIT_SPACE;
+}
@@ -0,0 +1,426 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{allocate, assign, transfer, Allocate, Assign, Transfer},
+};
+use anchor_spl::token::{
+    close_account, initialize_account3, spl_token::native_mint, sync_native, CloseAccount,
+    InitializeAccount3, SyncNative, TokenAccount,
+};
+
+use crate::{
+    error::ErrorCode, Agreement, BlockedWallet, CommissionPaid, Investor, ReferralFeePaid,
+    SellerBondReturned, AGREEMENT_SEED, BASIC_POINTS, UNWRAP_SEED,
+};
+
+/// Grows `account` to `new_len` bytes, topping up rent from `payer`. New bytes are zeroed.
+pub fn realloc_account<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if account.data_len() >= new_len {
+        return Ok(());
+    }
+
+    let rent_due = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if rent_due > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+
+    account.realloc(new_len, true)?;
+
+    Ok(())
+}
+
+/// Creates the PDA `account` signed by `seeds` with `space` bytes owned by `owner`, topping up
+/// rent from `payer`. Unlike `create_account` it succeeds when the address was already funded.
+pub fn create_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let rent_due = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if rent_due > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: account.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign {
+                account_to_assign: account.clone(),
+            },
+            &[seeds],
+        ),
+        owner,
+    )?;
+
+    Ok(())
+}
+
+/// Wraps `amount` lamports of `payer` into the native mint `token_account`.
+pub fn wrap_native<'info>(
+    payer: &AccountInfo<'info>,
+    token_account: &Account<'info, TokenAccount>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if token_account.mint != native_mint::ID {
+        return Err(ErrorCode::InvalidPaymentMint.into());
+    }
+
+    transfer(
+        CpiContext::new(
+            system_program.clone(),
+            Transfer {
+                from: payer.clone(),
+                to: token_account.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    sync_native(CpiContext::new(
+        token_program.clone(),
+        SyncNative {
+            account: token_account.to_account_info(),
+        },
+    ))?;
+
+    Ok(())
+}
+
+/// Pays `amount` out of the native mint `vault` as lamports to `destination`, through a
+/// temporary token account at `[UNWRAP_SEED, agreement, destination]` whose rent `payer`
+/// covers and gets back on close.
+#[allow(clippy::too_many_arguments)]
+pub fn unwrap_native<'info>(
+    agreement: &Account<'info, Agreement>,
+    vault: &Account<'info, TokenAccount>,
+    mint: &AccountInfo<'info>,
+    unwrap_account: &AccountInfo<'info>,
+    unwrap_bump: u8,
+    destination: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if vault.mint != native_mint::ID || *mint.key != native_mint::ID {
+        return Err(ErrorCode::InvalidDestination.into());
+    }
+
+    let agreement_key = agreement.key();
+    create_pda_account(
+        unwrap_account,
+        TokenAccount::LEN,
+        token_program.key,
+        &[
+            UNWRAP_SEED.as_bytes(),
+            agreement_key.as_ref(),
+            destination.key.as_ref(),
+            &[unwrap_bump],
+        ],
+        payer,
+        system_program,
+    )?;
+    initialize_account3(CpiContext::new(
+        token_program.clone(),
+        InitializeAccount3 {
+            account: unwrap_account.clone(),
+            mint: mint.clone(),
+            authority: agreement.to_account_info(),
+        },
+    ))?;
+
+    let tenant_seed = agreement.tenant_seed();
+    let agreement_id = agreement.agreement_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        AGREEMENT_SEED.as_bytes(),
+        tenant_seed.as_ref(),
+        agreement_id.as_ref(),
+        &[agreement.bump],
+    ]];
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            anchor_spl::token::Transfer {
+                from: vault.to_account_info(),
+                to: unwrap_account.clone(),
+                authority: agreement.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+    // Closing pays the rent and the unwrapped amount to `payer`, which forwards the amount
+    close_account(CpiContext::new_with_signer(
+        token_program.clone(),
+        CloseAccount {
+            account: unwrap_account.clone(),
+            destination: payer.clone(),
+            authority: agreement.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+    if destination.key != payer.key {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: destination.clone(),
+                },
+            ),
+            amount,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns the seller bond once tokens are delivered before the deadline.
+pub fn return_seller_bond<'info>(
+    agreement: &mut Account<'info, Agreement>,
+    bond_token_account: Option<&Account<'info, TokenAccount>>,
+    seller_payment_token_account: Option<&Account<'info, TokenAccount>>,
+    token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let (Some(bond_token_account), Some(seller_payment_token_account)) =
+        (bond_token_account, seller_payment_token_account)
+    else {
+        return Err(ErrorCode::SellerBondAccountsRequired.into());
+    };
+    if seller_payment_token_account.owner != agreement.token_seller
+        || seller_payment_token_account.mint != agreement.payment_token_mint
+    {
+        return Err(ErrorCode::InvalidDestination.into());
+    }
+
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            anchor_spl::token::Transfer {
+                from: bond_token_account.to_account_info(),
+                to: seller_payment_token_account.to_account_info(),
+                authority: agreement.to_account_info(),
+            },
+            &[&[
+                AGREEMENT_SEED.as_bytes(),
+                agreement.tenant_seed().as_ref(),
+                agreement.agreement_id.to_le_bytes().as_ref(),
+                &[agreement.bump],
+            ]],
+        ),
+        agreement.seller_bond,
+    )?;
+    agreement.bond_returned = true;
+
+    emit!(SellerBondReturned {
+        agreement: agreement.key(),
+        amount: agreement.seller_bond,
+    });
+
+    Ok(())
+}
+
+/// Pays `amount` of commission out of `vault`, whole to the treasury without a fee split,
+/// otherwise across the `Agreement::fee_split` recipients, each passed in `fee_accounts` as its
+/// token account and blocklist PDA, in order. A blocked recipient's share goes to the treasury.
+/// Returns what each recipient received.
+pub fn pay_commission<'info>(
+    agreement: &Account<'info, Agreement>,
+    vault: &Account<'info, TokenAccount>,
+    treasury: Pubkey,
+    treasury_token_account: Option<&Account<'info, TokenAccount>>,
+    fee_accounts: &'info [AccountInfo<'info>],
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<Vec<u64>> {
+    let tenant_seed = agreement.tenant_seed();
+    let agreement_id = agreement.agreement_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        AGREEMENT_SEED.as_bytes(),
+        tenant_seed.as_ref(),
+        agreement_id.as_ref(),
+        &[agreement.bump],
+    ]];
+    let pay = |to: AccountInfo<'info>, amount: u64| {
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                anchor_spl::token::Transfer {
+                    from: vault.to_account_info(),
+                    to,
+                    authority: agreement.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    };
+    let pay_treasury = |amount: u64| {
+        let treasury_token_account = treasury_token_account.ok_or(ErrorCode::InvalidDestination)?;
+        if treasury_token_account.owner != treasury {
+            return Err(ErrorCode::InvalidDestination.into());
+        }
+        pay(treasury_token_account.to_account_info(), amount)
+    };
+
+    if agreement.fee_split.is_empty() {
+        pay_treasury(amount)?;
+        return Ok(Vec::new());
+    }
+    if fee_accounts.len() != agreement.fee_split.len() * 2 {
+        return Err(ErrorCode::InvalidFeeSplit.into());
+    }
+
+    let mut unpaid_amount = amount;
+    let mut paid = Vec::with_capacity(agreement.fee_split.len());
+    for (index, (share, accounts)) in agreement
+        .fee_split
+        .iter()
+        .zip(fee_accounts.chunks(2))
+        .enumerate()
+    {
+        // The last recipient takes the rounding remainder
+        let share_amount = if index + 1 == agreement.fee_split.len() {
+            unpaid_amount
+        } else {
+            (amount as u128 * share.basis_points as u128 / BASIC_POINTS as u128) as u64
+        };
+        unpaid_amount -= share_amount;
+
+        if BlockedWallet::is_blocked(&accounts[1], &tenant_seed, &share.recipient)? {
+            pay_treasury(share_amount)?;
+            paid.push(0);
+            emit!(CommissionPaid {
+                agreement: agreement.key(),
+                recipient: treasury,
+                mint: vault.mint,
+                amount: share_amount,
+            });
+            continue;
+        }
+        let recipient_token_account = Account::<TokenAccount>::try_from(&accounts[0])?;
+        if recipient_token_account.owner != share.recipient
+            || recipient_token_account.mint != vault.mint
+        {
+            return Err(ErrorCode::InvalidDestination.into());
+        }
+        pay(recipient_token_account.to_account_info(), share_amount)?;
+        paid.push(share_amount);
+
+        emit!(CommissionPaid {
+            agreement: agreement.key(),
+            recipient: share.recipient,
+            mint: vault.mint,
+            amount: share_amount,
+        });
+    }
+
+    Ok(paid)
+}
+
+/// Pays the referrer of `investor` its share of `commission` out of `vault` and returns the
+/// fee. A blocked referrer is paid nothing, its share stays with the rest of the commission.
+pub fn pay_referral_fee<'info>(
+    agreement: &Account<'info, Agreement>,
+    vault: &Account<'info, TokenAccount>,
+    investor: &Investor,
+    referrer_token_account: Option<&AccountInfo<'info>>,
+    referrer_blocked_wallet: Option<&AccountInfo<'info>>,
+    token_program: &AccountInfo<'info>,
+    commission: u64,
+) -> Result<u64> {
+    if investor.referrer == Pubkey::default() {
+        return Ok(0);
+    }
+    let fee = (commission as u128 * investor.referral_bps as u128 / BASIC_POINTS as u128) as u64;
+    if fee == 0 {
+        return Ok(0);
+    }
+    let (Some(referrer_token_account), Some(referrer_blocked_wallet)) =
+        (referrer_token_account, referrer_blocked_wallet)
+    else {
+        return Err(ErrorCode::InvalidReferrerAccount.into());
+    };
+    if BlockedWallet::is_blocked(
+        referrer_blocked_wallet,
+        &agreement.tenant_seed(),
+        &investor.referrer,
+    )? {
+        return Ok(0);
+    }
+    if *referrer_token_account.owner != anchor_spl::token::ID {
+        return Err(ErrorCode::InvalidDestination.into());
+    }
+    let token_account =
+        TokenAccount::try_deserialize(&mut &referrer_token_account.data.borrow()[..])?;
+    if token_account.owner != investor.referrer || token_account.mint != vault.mint {
+        return Err(ErrorCode::InvalidDestination.into());
+    }
+
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            anchor_spl::token::Transfer {
+                from: vault.to_account_info(),
+                to: referrer_token_account.clone(),
+                authority: agreement.to_account_info(),
+            },
+            &[&[
+                AGREEMENT_SEED.as_bytes(),
+                agreement.tenant_seed().as_ref(),
+                agreement.agreement_id.to_le_bytes().as_ref(),
+                &[agreement.bump],
+            ]],
+        ),
+        fee,
+    )?;
+
+    emit!(ReferralFeePaid {
+        agreement: agreement.key(),
+        referrer: investor.referrer,
+        wallet: investor.wallet,
+        mint: vault.mint,
+        amount: fee,
+    });
+
+    Ok(fee)
+}
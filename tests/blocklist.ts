@@ -0,0 +1,467 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("blocklist", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+  let agreement: anchor.web3.PublicKey;
+  let paymentTokenAccount: anchor.web3.PublicKey;
+
+  // Creates an accepted agreement the seller has deposited into, so it takes deposits
+  const createAgreement = async (overrides = {}) => {
+    const agreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const agreement = seeds("agreement");
+
+    await program.methods.initializeAgreement(defaultAgreementParams(overrides)).accounts({
+      settings,
+      agreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    const sellerTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      tokenSeller.publicKey
+    )).address;
+    const companyTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      companyWallet.publicKey
+    )).address;
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: sellerTokenAccount,
+        lamports: 100,
+      }),
+      createSyncNativeInstruction(sellerTokenAccount)
+    ));
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement,
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount,
+      companyTokenAccount,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    return { agreement, paymentTokenAccount: seeds("payment"), projectTokenAccount: seeds("project") };
+  };
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+    ({ agreement, paymentTokenAccount } = await createAgreement());
+  });
+
+  it("Rejects deposits from a blocked wallet until it is unblocked", async () => {
+    const blocked = anchor.web3.Keypair.generate();
+    const signature = await program.provider.connection.requestAirdrop(blocked.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    const blockedWallet = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, blocked.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blockedTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      blocked.publicKey
+    )).address;
+    const companyTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      companyWallet.publicKey
+    )).address;
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: blockedTokenAccount,
+        lamports: 100,
+      }),
+      createSyncNativeInstruction(blockedTokenAccount)
+    ));
+
+    await program.methods.blockWallet({ wallet: blocked.publicKey }).accounts({
+      settings,
+      blockedWallet,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    const deposit = () => program.methods.depositStablecoins({ amount: new anchor.BN(100), memo: null, receiptNonce: null }).accounts({
+      agreement,
+      investor: null,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("payment_receipt"), agreement.toBuffer(), blocked.publicKey.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0],
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet,
+      destinationTokenAccount: companyTokenAccount,
+      payerTokenAccount: blockedTokenAccount,
+      payer: blocked.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([blocked]).rpc();
+
+    try {
+      await deposit();
+      assert.fail("blocked wallet deposited");
+    } catch (err) {
+      assert.include(err.toString(), "WalletBlocked");
+    }
+
+    await program.methods.unblockWallet({ wallet: blocked.publicKey }).accounts({
+      settings,
+      blockedWallet,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+    }).rpc();
+    assert.isNull(await program.account.blockedWallet.fetchNullable(blockedWallet));
+
+    await deposit();
+  });
+
+  it("Escrows the commitment refund of a blocked investor until it is released", async () => {
+    const investor = anchor.web3.Keypair.generate();
+    await airdrop(program, investor.publicKey);
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blockedWallet = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const investorTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      investor.publicKey
+    )).address;
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(300), tokenAmount: new anchor.BN(50), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.depositStablecoins({ amount: new anchor.BN(300), memo: null, receiptNonce: null }).accounts({
+      agreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet,
+      destinationTokenAccount: paymentTokenAccount,
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    await program.methods.blockWallet({ wallet: investor.publicKey }).accounts({
+      settings,
+      blockedWallet,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    await program.methods.investorWithdrawCommitment({}).accounts({
+      agreement,
+      investor: investorAccount,
+      referrer: null,
+      blockedWallet,
+      paymentTokenAccount,
+      investorTokenAccount,
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      rentPayer: program.provider.wallet.publicKey,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).signers([investor]).rpc();
+
+    const investorState = await program.account.investor.fetch(investorAccount);
+    assert.equal(investorState.escrowedAmount.toNumber(), 300);
+    assert.isTrue(investorState.refunded);
+    assert.isFalse(investorState.investorDeposited);
+
+    await program.methods.unblockWallet({ wallet: investor.publicKey }).accounts({
+      settings,
+      blockedWallet,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+    }).rpc();
+
+    const before = await program.provider.connection.getTokenAccountBalance(investorTokenAccount);
+    await program.methods.releaseEscrow({}).accounts({
+      settings,
+      agreement,
+      investor: investorAccount,
+      blockedWallet,
+      agreementTokenAccount: paymentTokenAccount,
+      destinationTokenAccount: investorTokenAccount,
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).rpc();
+    const after = await program.provider.connection.getTokenAccountBalance(investorTokenAccount);
+    assert.equal(Number(after.value.amount) - Number(before.value.amount), 300);
+  });
+
+  it("Rejects a deposit paid by another wallet for a blocked investor", async () => {
+    const investor = anchor.web3.Keypair.generate();
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blockedWallet = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(300), tokenAmount: new anchor.BN(50), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.blockWallet({ wallet: investor.publicKey }).accounts({
+      settings,
+      blockedWallet,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    const deposit = (blocklistEntry: anchor.web3.PublicKey) => program.methods.depositStablecoins({ amount: new anchor.BN(300), memo: null, receiptNonce: null }).accounts({
+      agreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet: blocklistEntry,
+      destinationTokenAccount: paymentTokenAccount,
+      payerTokenAccount: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).rpc();
+
+    try {
+      await deposit(blockedWallet);
+      assert.fail("deposit for a blocked investor accepted");
+    } catch (err) {
+      assert.include(err.toString(), "WalletBlocked");
+    }
+
+    // The payer's own, empty, blocklist entry does not stand in for the investor's
+    try {
+      await deposit(anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("blocklist"), tenantSeed, program.provider.wallet.publicKey.toBuffer()],
+        program.programId
+      )[0]);
+      assert.fail("blocklist entry of the payer accepted");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidBlocklistAccount");
+    }
+  });
+
+  it("Escrows the batch cancellation refund of a blocked investor until it is released", async () => {
+    const fundingDeadline = Math.floor(Date.now() / 1000) + 5;
+    const { agreement, paymentTokenAccount, projectTokenAccount } = await createAgreement({
+      softCap: new anchor.BN(600),
+      fundingDeadline: new anchor.BN(fundingDeadline),
+    });
+    const investor = anchor.web3.Keypair.generate();
+    await airdrop(program, investor.publicKey);
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blockedWallet = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const investorTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      investor.publicKey
+    )).address;
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(300), tokenAmount: new anchor.BN(50), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.depositStablecoins({ amount: new anchor.BN(300), memo: null, receiptNonce: null }).accounts({
+      agreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet,
+      destinationTokenAccount: paymentTokenAccount,
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    await program.methods.blockWallet({ wallet: investor.publicKey }).accounts({
+      settings,
+      blockedWallet,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    // Soft cap missed by the deadline
+    await new Promise(resolve => setTimeout(resolve, (fundingDeadline + 2) * 1000 - Date.now()));
+    await program.methods.cancelAgreement({}).accounts({
+      settings,
+      agreement,
+      projectTokenAccount,
+      investor: null,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).rpc();
+
+    const withdrawBatch = (destination: anchor.web3.PublicKey) => program.methods.withdrawCancelledFundsBatch({}).accounts({
+      agreement,
+      paymentTokenAccount,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).remainingAccounts([
+      { pubkey: investorAccount, isWritable: true, isSigner: false },
+      { pubkey: destination, isWritable: true, isSigner: false },
+      { pubkey: blockedWallet, isWritable: false, isSigner: false },
+    ]).rpc();
+
+    try {
+      await withdrawBatch(paymentTokenAccount);
+      assert.fail("refund to a token account of another wallet accepted");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidDestination");
+    }
+    await withdrawBatch(investorTokenAccount);
+
+    const investorState = await program.account.investor.fetch(investorAccount);
+    assert.equal(investorState.escrowedAmount.toNumber(), 300);
+    assert.isTrue(investorState.refunded);
+    assert.isFalse(investorState.investorDeposited);
+
+    await program.methods.unblockWallet({ wallet: investor.publicKey }).accounts({
+      settings,
+      blockedWallet,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+    }).rpc();
+
+    const before = await program.provider.connection.getTokenAccountBalance(investorTokenAccount);
+    await program.methods.releaseEscrow({}).accounts({
+      settings,
+      agreement,
+      investor: investorAccount,
+      blockedWallet,
+      agreementTokenAccount: paymentTokenAccount,
+      destinationTokenAccount: investorTokenAccount,
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).rpc();
+    const after = await program.provider.connection.getTokenAccountBalance(investorTokenAccount);
+    assert.equal(Number(after.value.amount) - Number(before.value.amount), 300);
+  });
+});
@@ -3,6 +3,7 @@ import { Program } from "@coral-xyz/anchor";
 import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
 import { TOKEN_PROGRAM_ID, NATIVE_MINT, createAssociatedTokenAccount, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount, transfer } from '@solana/spl-token';
 import { assert } from "chai";
+import { defaultAgreementParams } from "./utils";
 
 describe("cancelled-agreement", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
@@ -18,28 +19,29 @@ describe("cancelled-agreement", () => {
     anchor.web3.Keypair.generate(),
   ];
 
-  const agreementId = new anchor.BN(2);
   const expectedPayment = new anchor.BN(600);
   const expectedTokens = new anchor.BN(100);
   const holdDuration = new anchor.BN(1);
+  const documentHash = Array(32).fill(7);
 
   const paymentTokenMint = NATIVE_MINT;
   const projectTokenMint = NATIVE_MINT;
   const recipientWallet = anchor.web3.PublicKey.unique();
-  const agreement = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("agreement"), Uint8Array.from(agreementId.toBuffer('le', 8))],
-    program.programId
-  )[0];
-  const paymentTokenAccount = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("payment"), Uint8Array.from(agreementId.toBuffer('le', 8))],
+  // Assigned by the settings counter when the agreement is initialized
+  let agreementId: anchor.BN;
+  let agreement: anchor.web3.PublicKey;
+  let paymentTokenAccount: anchor.web3.PublicKey;
+  let projectTokenAccount: anchor.web3.PublicKey;
+  const settings = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("settings")],
     program.programId
   )[0];
-  const projectTokenAccount = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("project"), Uint8Array.from(agreementId.toBuffer('le', 8))],
-    program.programId
+  const programData = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
   )[0];
-  const settings = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("settings")],
+  const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("blocklist"), wallet.toBuffer()],
     program.programId
   )[0];
 
@@ -47,17 +49,20 @@ describe("cancelled-agreement", () => {
     {
       amount: new anchor.BN(100),
       tokenAmount: new anchor.BN(20),
-      wallet: investors[0].publicKey
+      wallet: investors[0].publicKey,
+      referral: null
     },
     {
       amount: new anchor.BN(200),
       tokenAmount: new anchor.BN(30),
-      wallet: investors[1].publicKey
+      wallet: investors[1].publicKey,
+      referral: null
     },
     {
       amount: new anchor.BN(300),
       tokenAmount: new anchor.BN(50),
-      wallet: investors[2].publicKey
+      wallet: investors[2].publicKey,
+      referral: null
     }
   ]
 
@@ -145,8 +150,13 @@ describe("cancelled-agreement", () => {
         commissionBasisPoints: new anchor.BN(100), // 1%
         cancelTimeout: new anchor.BN(1),
         owner: program.provider.wallet.publicKey,
+        tenantId: new anchor.BN(0),
+        allowedMints: [],
       }).accounts({
         settings,
+        program: program.programId,
+        programData,
+        authority: program.provider.wallet.publicKey,
         payer: program.provider.wallet.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       }).rpc();
@@ -158,12 +168,27 @@ describe("cancelled-agreement", () => {
   });
 
   it("Initializes the agreement", async () => {
-    const tx = await program.methods.initializeAgreement({
-      agreementId,
+    agreementId = (await program.account.settings.fetch(settings)).nextAgreementId;
+    agreement = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("agreement"), Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    paymentTokenAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("payment"), Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    projectTokenAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("project"), Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+
+    const tx = await program.methods.initializeAgreement(defaultAgreementParams({
       expectedPayment,
       expectedTokens,
       holdDuration,
-    }).accounts({
+      documentHash,
+      documentUri: "https://example.com/saft.pdf",
+    })).accounts({
       settings,
       agreement,
       paymentTokenMint,
@@ -211,12 +236,35 @@ describe("cancelled-agreement", () => {
     }
   });
 
+  it("Accepts the agreement by seller", async () => {
+    const tx = await program.methods.acceptAgreement({
+      expectedPayment,
+      expectedTokens,
+      holdDuration,
+      paymentTokenMint,
+      projectTokenMint,
+      documentHash,
+    }).accounts({
+      agreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+    console.log("Your transaction signature", tx);
+
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.isTrue(agreementAccount.sellerAccepted);
+  });
+
   it("Deposits tokens by seller", async () => {
     await transfer(program.provider.connection, tokenSeller, sellerTokenAccount, projectTokenAccount, tokenSeller.publicKey, expectedTokens.toNumber());
 
     const tx = await program.methods.processTokenDeposit({}).accounts({
       agreement,
       agreementTokenAccount: projectTokenAccount,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
       payer: tokenSeller.publicKey,
       systemProgram: anchor.web3.SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
@@ -225,20 +273,48 @@ describe("cancelled-agreement", () => {
     console.log("Your transaction signature", tx);
   });
 
+  it("Acknowledges the document by investors", async () => {
+    for (const investor of investors) {
+      const tx = await program.methods.acknowledgeDocument({ documentHash }).accounts({
+        agreement,
+        investor: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        acknowledgement: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("acknowledgement"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        payer: investor.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      }).signers([investor]).rpc();
+      console.log("Your transaction signature", tx);
+    }
+  });
+
   it("Deposits stablecoins by part of investors", async () => {
     for (var i = 0; i < investors.length - 1; i++) {
       const allocation = allocations[i];
       const investor = investors[i];
       const investorTokenAccount = investorTokenAccounts[i];
 
-      const tx = await program.methods.depositStablecoins({ amount: allocation.amount }).accounts({
+      const tx = await program.methods.depositStablecoins({ amount: allocation.amount, memo: null, receiptNonce: null }).accounts({
         agreement,
         investor: anchor.web3.PublicKey.findProgramAddressSync(
           [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
           program.programId
         )[0],
+        acknowledgement: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("acknowledgement"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        attestation: null,
+        paymentReceipt: null,
+        paymentMint: null,
+        priceFeed: null,
         destinationTokenAccount: paymentTokenAccount,
         payerTokenAccount: investorTokenAccount,
+        blockedWallet: blocklist(investor.publicKey),
         payer: investor.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
@@ -249,13 +325,79 @@ describe("cancelled-agreement", () => {
     }
   });
 
+  it("Withdraws the commitment of one investor and reassigns the allocation", async () => {
+    const allocation = allocations[1];
+    const investor = investors[1];
+    const investorTokenAccount = investorTokenAccounts[1];
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const fundedAgreement = await program.account.agreement.fetch(agreement);
+
+    const tx = await program.methods.investorWithdrawCommitment({}).accounts({
+      agreement,
+      investor: investorAccount,
+      referrer: null,
+      blockedWallet: blocklist(investor.publicKey),
+      paymentTokenAccount,
+      investorTokenAccount,
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      rentPayer: program.provider.wallet.publicKey,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).signers([investor]).rpc();
+    console.log("Your transaction signature", tx);
+
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.equal(agreementAccount.totalInvested.toString(), fundedAgreement.totalInvested.sub(allocation.amount).toString());
+    assert.equal(agreementAccount.totalRequired.toString(), fundedAgreement.totalRequired.sub(allocation.amount).toString());
+    assert.equal(agreementAccount.investorsCount.toNumber(), fundedAgreement.investorsCount.toNumber() - 1);
+    assert.isNull(await program.account.investor.fetchNullable(investorAccount));
+
+    // the freed allocation can be assigned again after the seller deposit
+    await program.methods.addInvestors({ allocations: [allocation] }).accounts({
+      agreement,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.depositStablecoins({ amount: allocation.amount, memo: null, receiptNonce: null }).accounts({
+      agreement,
+      investor: investorAccount,
+      acknowledgement: anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("acknowledgement"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+        program.programId
+      )[0],
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      destinationTokenAccount: paymentTokenAccount,
+      payerTokenAccount: investorTokenAccount,
+      blockedWallet: blocklist(investor.publicKey),
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    const refundedAgreement = await program.account.agreement.fetch(agreement);
+    assert.equal(refundedAgreement.totalInvested.toString(), fundedAgreement.totalInvested.toString());
+  });
+
   it("Cancel agreement", async () => {
     await sleep(1000);
 
     const tx = await program.methods.cancelAgreement({}).accounts({
+      settings,
       agreement,
       projectTokenAccount,
       investor: null,
+      proposal: null,
       payer: program.provider.wallet.publicKey,
       clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
     }).rpc();
@@ -276,6 +418,10 @@ describe("cancelled-agreement", () => {
         )[0],
         agreementTokenAccount: paymentTokenAccount,
         destinationTokenAccount: investorTokenAccount,
+        unwrapTokenAccount: null,
+        nativeMint: null,
+        destination: null,
+        blockedWallet: blocklist(investor.publicKey),
         payer: investor.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
@@ -292,6 +438,10 @@ describe("cancelled-agreement", () => {
       investor: null,
       agreementTokenAccount: projectTokenAccount,
       destinationTokenAccount: sellerTokenAccount,
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      blockedWallet: blocklist(tokenSeller.publicKey),
       payer: tokenSeller.publicKey,
       systemProgram: anchor.web3.SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
@@ -3,6 +3,7 @@ import { Program } from "@coral-xyz/anchor";
 import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
 import { TOKEN_PROGRAM_ID, NATIVE_MINT, createAssociatedTokenAccount, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount, transfer } from '@solana/spl-token';
 import { assert } from "chai";
+import { defaultAgreementParams } from "./utils";
 
 describe("completed-agreement", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
@@ -18,28 +19,29 @@ describe("completed-agreement", () => {
     anchor.web3.Keypair.generate(),
   ];
 
-  const agreementId = new anchor.BN(1);
   const expectedPayment = new anchor.BN(600);
   const expectedTokens = new anchor.BN(100);
   const holdDuration = new anchor.BN(1);
+  const documentHash = Array(32).fill(7);
 
   const paymentTokenMint = NATIVE_MINT;
   const projectTokenMint = NATIVE_MINT;
   const recipientWallet = anchor.web3.PublicKey.unique();
-  const agreement = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("agreement"), Uint8Array.from(agreementId.toBuffer('le', 8))],
+  // Assigned by the settings counter when the agreement is initialized
+  let agreementId: anchor.BN;
+  let agreement: anchor.web3.PublicKey;
+  let paymentTokenAccount: anchor.web3.PublicKey;
+  let projectTokenAccount: anchor.web3.PublicKey;
+  const settings = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("settings")],
     program.programId
   )[0];
-  const paymentTokenAccount = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("payment"), Uint8Array.from(agreementId.toBuffer('le', 8))],
-    program.programId
+  const programData = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
   )[0];
-  const projectTokenAccount = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("project"), Uint8Array.from(agreementId.toBuffer('le', 8))],
-    program.programId
-  )[0];
-  const settings = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("settings")],
+  const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("blocklist"), wallet.toBuffer()],
     program.programId
   )[0];
 
@@ -47,17 +49,20 @@ describe("completed-agreement", () => {
     {
       amount: new anchor.BN(100),
       tokenAmount: new anchor.BN(20),
-      wallet: investors[0].publicKey
+      wallet: investors[0].publicKey,
+      referral: null
     },
     {
       amount: new anchor.BN(200),
       tokenAmount: new anchor.BN(30),
-      wallet: investors[1].publicKey
+      wallet: investors[1].publicKey,
+      referral: null
     },
     {
       amount: new anchor.BN(300),
       tokenAmount: new anchor.BN(50),
-      wallet: investors[2].publicKey
+      wallet: investors[2].publicKey,
+      referral: null
     }
   ]
 
@@ -160,8 +165,13 @@ describe("completed-agreement", () => {
         commissionBasisPoints: new anchor.BN(100), // 1%
         cancelTimeout: new anchor.BN(1),
         owner: program.provider.wallet.publicKey,
+        tenantId: new anchor.BN(0),
+        allowedMints: [],
       }).accounts({
         settings,
+        program: program.programId,
+        programData,
+        authority: program.provider.wallet.publicKey,
         payer: program.provider.wallet.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       }).rpc();
@@ -173,12 +183,27 @@ describe("completed-agreement", () => {
   });
 
   it("Initializes the agreement", async () => {
-    const tx = await program.methods.initializeAgreement({
-      agreementId,
+    agreementId = (await program.account.settings.fetch(settings)).nextAgreementId;
+    agreement = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("agreement"), Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    paymentTokenAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("payment"), Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    projectTokenAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("project"), Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+
+    const tx = await program.methods.initializeAgreement(defaultAgreementParams({
       expectedPayment,
       expectedTokens,
       holdDuration,
-    }).accounts({
+      documentHash,
+      documentUri: "https://example.com/saft.pdf",
+    })).accounts({
       settings,
       agreement,
       paymentTokenMint,
@@ -227,12 +252,35 @@ describe("completed-agreement", () => {
     }
   });
 
+  it("Accepts the agreement by seller", async () => {
+    const tx = await program.methods.acceptAgreement({
+      expectedPayment,
+      expectedTokens,
+      holdDuration,
+      paymentTokenMint,
+      projectTokenMint,
+      documentHash,
+    }).accounts({
+      agreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+    console.log("Your transaction signature", tx);
+
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.isTrue(agreementAccount.sellerAccepted);
+  });
+
   it("Deposits tokens by seller", async () => {
     await transfer(program.provider.connection, tokenSeller, sellerTokenAccount, projectTokenAccount, tokenSeller.publicKey, expectedTokens.toNumber());
 
     const tx = await program.methods.processTokenDeposit({}).accounts({
       agreement,
       agreementTokenAccount: projectTokenAccount,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
       payer: tokenSeller.publicKey,
       systemProgram: anchor.web3.SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
@@ -241,20 +289,48 @@ describe("completed-agreement", () => {
     console.log("Your transaction signature", tx);
   });
 
+  it("Acknowledges the document by investors", async () => {
+    for (const investor of investors) {
+      const tx = await program.methods.acknowledgeDocument({ documentHash }).accounts({
+        agreement,
+        investor: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        acknowledgement: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("acknowledgement"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        payer: investor.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      }).signers([investor]).rpc();
+      console.log("Your transaction signature", tx);
+    }
+  });
+
   it("Deposits stablecoins by investors", async () => {
     for (var i = 0; i < investors.length; i++) {
       const allocation = allocations[i];
       const investor = investors[i];
       const investorTokenAccount = investorTokenAccounts[i];
 
-      const tx = await program.methods.depositStablecoins({ amount: allocation.amount }).accounts({
+      const tx = await program.methods.depositStablecoins({ amount: allocation.amount, memo: null, receiptNonce: null }).accounts({
         agreement,
         investor: anchor.web3.PublicKey.findProgramAddressSync(
           [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
           program.programId
         )[0],
+        acknowledgement: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("acknowledgement"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        attestation: null,
+        paymentReceipt: null,
+        paymentMint: null,
+        priceFeed: null,
         destinationTokenAccount: paymentTokenAccount,
         payerTokenAccount: investorTokenAccount,
+        blockedWallet: blocklist(investor.publicKey),
         payer: investor.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
@@ -270,11 +346,21 @@ describe("completed-agreement", () => {
   });
 
   it("Deposits stablecoins by non-investor", async () => {
-    const tx = await program.methods.depositStablecoins({ amount: new anchor.BN(150) }).accounts({
+    const paymentReceipt = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("payment_receipt"), agreement.toBuffer(), otherWallet.publicKey.toBuffer(), new anchor.BN(7).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+    const tx = await program.methods.depositStablecoins({ amount: new anchor.BN(150), memo: "SL-1", receiptNonce: new anchor.BN(7) }).accounts({
       agreement,
       investor: null,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt,
+      paymentMint: null,
+      priceFeed: null,
       destinationTokenAccount: companyPaymentTokenAccount,
       payerTokenAccount: otherTokenAccount,
+      blockedWallet: blocklist(otherWallet.publicKey),
       payer: otherWallet.publicKey,
       systemProgram: anchor.web3.SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
@@ -282,6 +368,15 @@ describe("completed-agreement", () => {
     }).signers([otherWallet]).rpc();
 
     console.log("Your transaction signature", tx);
+
+    const receipt = await program.account.paymentReceipt.fetch(paymentReceipt);
+    assert.ok(receipt.payer.equals(otherWallet.publicKey));
+    assert.equal(receipt.amount.toNumber(), 150);
+    assert.equal(receipt.memo, "SL-1");
+    assert.equal(receipt.nonce.toNumber(), 7);
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.equal(agreementAccount.directPaymentsTotal.toNumber(), 150);
+    assert.equal(agreementAccount.paymentReceiptsCount.toNumber(), 1);
   });
 
   it("Withdraws tokens by investors", async () => {
@@ -297,10 +392,14 @@ describe("completed-agreement", () => {
           [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
           program.programId
         )[0],
+        attestation: null,
+        referrerBlockedWallet: null,
+        referrerTokenAccount: null,
         settings,
         agreementTokenAccount: projectTokenAccount,
         investorTokenAccount,
         ownerTokenAccount,
+        blockedWallet: blocklist(investor.publicKey),
         payer: investor.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
@@ -314,9 +413,14 @@ describe("completed-agreement", () => {
   it("Withdraws funds", async () => {
     const tx = await program.methods.withdrawFunds({}).accounts({
       agreement,
-      agreementTokenAccount: paymentTokenAccount,
+      paymentMint: null,
+      paymentTokenAccount,
       sellerTokenAccount,
       ownerTokenAccount,
+      blockedWallet: blocklist(tokenSeller.publicKey),
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      tokenSeller: null,
       payer: tokenSeller.publicKey,
       systemProgram: anchor.web3.SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
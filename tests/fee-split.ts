@@ -0,0 +1,311 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("fee-split", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Splits commission across the platform fee recipients", async () => {
+    const feeAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(feeAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const feeAgreement = seeds("agreement");
+    const partner = anchor.web3.Keypair.generate();
+    const investor = anchor.web3.Keypair.generate();
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), feeAgreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const signature = await program.provider.connection.requestAirdrop(investor.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    const updateFeeSplit = (feeSplit: { recipient: anchor.web3.PublicKey, basisPoints: number }[]) =>
+      program.methods.updateSettings({
+        cancelTimeout: null,
+        commissionBasisPoints: null,
+        owner: null,
+        allowedMints: null,
+        treasury: null,
+        multisigSigners: null,
+        multisigThreshold: null,
+        feeSplit,
+      }).accounts({
+        settings,
+        proposal: null,
+        payer: program.provider.wallet.publicKey,
+      }).rpc();
+
+    try {
+      await updateFeeSplit([{ recipient: partner.publicKey, basisPoints: 4000 }]);
+      assert.fail("fee split not adding up to 100% accepted");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidFeeSplit");
+    }
+    await updateFeeSplit([
+      { recipient: program.provider.wallet.publicKey, basisPoints: 6000 },
+      { recipient: partner.publicKey, basisPoints: 4000 },
+    ]);
+
+    await program.methods.initializeAgreement(defaultAgreementParams()).accounts({
+      settings,
+      agreement: feeAgreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+    // Agreements keep the table they were created with
+    await updateFeeSplit([]);
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(600), tokenAmount: new anchor.BN(100), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement: feeAgreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: feeAgreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    await program.methods.depositStablecoins({ amount: new anchor.BN(600), memo: null, receiptNonce: null }).accounts({
+      agreement: feeAgreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet: blocklist(investor.publicKey),
+      destinationTokenAccount: seeds("payment"),
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    const tokenAccount = async (wallet: anchor.web3.PublicKey) => (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      wallet
+    )).address;
+    const treasuryTokenAccount = await tokenAccount(program.provider.wallet.publicKey);
+    const partnerTokenAccount = await tokenAccount(partner.publicKey);
+    const treasuryBefore = await program.provider.connection.getTokenAccountBalance(treasuryTokenAccount);
+
+    await program.methods.withdrawFunds({}).accounts({
+      settings,
+      agreement: feeAgreement,
+      paymentMint: null,
+      paymentTokenAccount: seeds("payment"),
+      blockedWallet: blocklist(tokenSeller.publicKey),
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      tokenSeller: null,
+      sellerTokenAccount: await tokenAccount(tokenSeller.publicKey),
+      ownerTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).remainingAccounts([
+      { pubkey: treasuryTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(program.provider.wallet.publicKey), isWritable: false, isSigner: false },
+      { pubkey: partnerTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(partner.publicKey), isWritable: false, isSigner: false },
+    ]).signers([tokenSeller]).rpc();
+
+    // 2.5% of 600 is 15, the partner's 40% rounds down
+    const treasuryAfter = await program.provider.connection.getTokenAccountBalance(treasuryTokenAccount);
+    const partnerBalance = await program.provider.connection.getTokenAccountBalance(partnerTokenAccount);
+    assert.equal(Number(treasuryAfter.value.amount) - Number(treasuryBefore.value.amount), 9);
+    assert.equal(partnerBalance.value.amount, "6");
+
+    const agreementAccount = await program.account.agreement.fetch(feeAgreement);
+    assert.deepEqual(agreementAccount.feeSplit.map((share) => share.fundsCollected.toNumber()), [9, 6]);
+    assert.equal(agreementAccount.fundsCommissionPaid.toNumber(), 15);
+    const settingsAccount = await program.account.settings.fetch(settings);
+    assert.isEmpty(settingsAccount.feeSplit);
+  });
+
+  it("Pays the share of a blocked fee recipient to the treasury", async () => {
+    const agreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const agreement = seeds("agreement");
+    const partner = anchor.web3.Keypair.generate();
+    const investor = anchor.web3.Keypair.generate();
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    await airdrop(program, investor.publicKey);
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      feeSplit: [
+        { recipient: program.provider.wallet.publicKey, basisPoints: 6000 },
+        { recipient: partner.publicKey, basisPoints: 4000 },
+      ],
+    })).accounts({
+      settings,
+      agreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(600), tokenAmount: new anchor.BN(100), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    await program.methods.depositStablecoins({ amount: new anchor.BN(600), memo: null, receiptNonce: null }).accounts({
+      agreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet: blocklist(investor.publicKey),
+      destinationTokenAccount: seeds("payment"),
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    await program.methods.blockWallet({ wallet: partner.publicKey }).accounts({
+      settings,
+      blockedWallet: blocklist(partner.publicKey),
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    const tokenAccount = async (wallet: anchor.web3.PublicKey) => (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      wallet
+    )).address;
+    const treasuryTokenAccount = await tokenAccount(program.provider.wallet.publicKey);
+    const partnerTokenAccount = await tokenAccount(partner.publicKey);
+    const withdrawFunds = (ownerTokenAccount: anchor.web3.PublicKey | null) => program.methods.withdrawFunds({}).accounts({
+      settings,
+      agreement,
+      paymentMint: null,
+      paymentTokenAccount: seeds("payment"),
+      blockedWallet: blocklist(tokenSeller.publicKey),
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      tokenSeller: null,
+      sellerTokenAccount: await tokenAccount(tokenSeller.publicKey),
+      ownerTokenAccount,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).remainingAccounts([
+      { pubkey: treasuryTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(program.provider.wallet.publicKey), isWritable: false, isSigner: false },
+      { pubkey: partnerTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(partner.publicKey), isWritable: false, isSigner: false },
+    ]).signers([tokenSeller]).rpc();
+
+    // The blocked partner's share needs the treasury token account
+    try {
+      await withdrawFunds(null);
+      assert.fail("blocked fee recipient paid");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidDestination");
+    }
+    const treasuryBefore = await program.provider.connection.getTokenAccountBalance(treasuryTokenAccount);
+    await withdrawFunds(treasuryTokenAccount);
+
+    const treasuryAfter = await program.provider.connection.getTokenAccountBalance(treasuryTokenAccount);
+    assert.equal(Number(treasuryAfter.value.amount) - Number(treasuryBefore.value.amount), 15);
+    assert.equal((await program.provider.connection.getTokenAccountBalance(partnerTokenAccount)).value.amount, "0");
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.deepEqual(agreementAccount.feeSplit.map((share) => share.fundsCollected.toNumber()), [9, 0]);
+    assert.equal(agreementAccount.fundsCommissionPaid.toNumber(), 15);
+  });
+});
@@ -0,0 +1,134 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { NATIVE_MINT } from '@solana/spl-token';
+import { assert } from "chai";
+
+// The legacy accounts are loaded by the test validator from tests/fixtures, see Anchor.toml
+describe("migration", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const agreementId = new anchor.BN(900);
+  const agreement = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("agreement"), Uint8Array.from(agreementId.toBuffer('le', 8))],
+    program.programId
+  )[0];
+  const investor = new anchor.web3.PublicKey("SofoQtdQCjXTSzzNhxJnnUrULrzt2at666Nqs6gu6LC");
+  const investorWallet = new anchor.web3.PublicKey("GuXwin5cfsKaU9vTpcjHEZJjDznkbz16jPRwMoWrkVXQ");
+  const settings = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("settings")],
+    program.programId
+  )[0];
+  const programData = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  )[0];
+
+  before(async () => {
+    let settingsAccount = await program.account.settings.fetchNullable(settings);
+    if (settingsAccount == null) {
+      await program.methods.initialize({
+        commissionBasisPoints: new anchor.BN(100), // 1%
+        cancelTimeout: new anchor.BN(1),
+        owner: program.provider.wallet.publicKey,
+        tenantId: new anchor.BN(0),
+        allowedMints: [],
+      }).accounts({
+        settings,
+        program: program.programId,
+        programData,
+        authority: program.provider.wallet.publicKey,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      }).rpc();
+    }
+  });
+
+  it("Migrates a legacy agreement", async () => {
+    const tx = await program.methods.migrateAgreement({}).accounts({
+      settings,
+      agreement,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+    console.log("Your transaction signature", tx);
+
+    const agreementInfo = await program.provider.connection.getAccountInfo(agreement);
+    assert.equal(agreementInfo.data.length, program.account.agreement.size);
+
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.equal(agreementAccount.version, 1);
+    assert.equal(agreementAccount.agreementId.toNumber(), 900);
+    assert.equal(agreementAccount.paymentTokenMint.toBase58(), NATIVE_MINT.toBase58());
+    assert.equal(agreementAccount.projectTokenMint.toBase58(), NATIVE_MINT.toBase58());
+    assert.equal(agreementAccount.expectedPayment.toNumber(), 600);
+    assert.equal(agreementAccount.expectedTokens.toNumber(), 100);
+    assert.equal(agreementAccount.totalInvested.toNumber(), 100);
+    assert.equal(agreementAccount.totalRequired.toNumber(), 100);
+    assert.equal(agreementAccount.totalTokenAllocation.toNumber(), 20);
+    assert.equal(agreementAccount.holdDuration.toNumber(), 1);
+    assert.equal(agreementAccount.investorsCount.toNumber(), 1);
+    assert.equal(agreementAccount.tenantId.toNumber(), 0);
+    assert.isFalse(agreementAccount.acceptanceRequired);
+    assert.equal(agreementAccount.documentUri, "");
+    assert.equal(agreementAccount.revenueSplit.length, 0);
+    assert.equal(agreementAccount.feeSplit.length, 0);
+  });
+
+  it("Cannot migrate an agreement twice", async () => {
+    try {
+      await program.methods.migrateAgreement({}).accounts({
+        settings,
+        agreement,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      }).rpc();
+      assert.fail("Agreement should not migrate twice");
+    } catch (err) {
+      assert.include(err.toString(), "AlreadyMigrated");
+    }
+  });
+
+  it("Migrates a legacy investor", async () => {
+    const tx = await program.methods.migrateInvestor({}).accounts({
+      settings,
+      agreement,
+      investor,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+    console.log("Your transaction signature", tx);
+
+    const investorInfo = await program.provider.connection.getAccountInfo(investor);
+    assert.equal(investorInfo.data.length, program.account.investor.size);
+
+    const investorAccount = await program.account.investor.fetch(investor);
+    assert.equal(investorAccount.version, 1);
+    assert.equal(investorAccount.wallet.toBase58(), investorWallet.toBase58());
+    assert.equal(investorAccount.agreement.toBase58(), agreement.toBase58());
+    assert.equal(investorAccount.tokenAllocation.toNumber(), 20);
+    assert.equal(investorAccount.requiredAmount.toNumber(), 100);
+    assert.isFalse(investorAccount.investorDeposited);
+    assert.isFalse(investorAccount.hasWithdrawnTokens);
+    assert.equal(investorAccount.escrowedAmount.toNumber(), 0);
+    assert.equal(investorAccount.paymentMint.toBase58(), anchor.web3.PublicKey.default.toBase58());
+    assert.equal(investorAccount.referrer.toBase58(), anchor.web3.PublicKey.default.toBase58());
+  });
+
+  it("Cannot migrate an investor twice", async () => {
+    try {
+      await program.methods.migrateInvestor({}).accounts({
+        settings,
+        agreement,
+        investor,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      }).rpc();
+      assert.fail("Investor should not migrate twice");
+    } catch (err) {
+      assert.include(err.toString(), "AlreadyMigrated");
+    }
+  });
+});
@@ -0,0 +1,353 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("milestones", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Releases proceeds per approved milestone and settles the rest on termination", async () => {
+    const milestoneAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(milestoneAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const milestoneAgreement = seeds("agreement");
+    const arbiter = anchor.web3.Keypair.generate();
+    const investor = anchor.web3.Keypair.generate();
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), milestoneAgreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const signature = await program.provider.connection.requestAirdrop(investor.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      holdDuration: new anchor.BN(3600),
+    })).accounts({
+      settings,
+      agreement: milestoneAgreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    const milestonePlan = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("milestones"), milestoneAgreement.toBuffer()],
+      program.programId
+    )[0];
+    await program.methods.setMilestones({
+      milestones: [
+        { basisPoints: 6000, approver: companyWallet.publicKey },
+        { basisPoints: 4000, approver: arbiter.publicKey },
+      ],
+    }).accounts({
+      settings,
+      agreement: milestoneAgreement,
+      milestonePlan,
+      operator: null,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(600), tokenAmount: new anchor.BN(100), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement: milestoneAgreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(3600),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: milestoneAgreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    const sellerTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      tokenSeller.publicKey
+    )).address;
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: sellerTokenAccount,
+        lamports: 100,
+      }),
+      createSyncNativeInstruction(sellerTokenAccount)
+    ));
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement: milestoneAgreement,
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount,
+      companyTokenAccount: (await getOrCreateAssociatedTokenAccount(
+        program.provider.connection,
+        program.provider.wallet.payer,
+        NATIVE_MINT,
+        companyWallet.publicKey
+      )).address,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    await program.methods.depositStablecoins({ amount: new anchor.BN(600), memo: null, receiptNonce: null }).accounts({
+      agreement: milestoneAgreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet: blocklist(investor.publicKey),
+      destinationTokenAccount: seeds("payment"),
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    const treasuryTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      program.provider.wallet.publicKey
+    )).address;
+    const withdrawFunds = () => program.methods.withdrawFunds({}).accounts({
+      settings,
+      agreement: milestoneAgreement,
+      paymentMint: null,
+      paymentTokenAccount: seeds("payment"),
+      blockedWallet: blocklist(tokenSeller.publicKey),
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      tokenSeller: null,
+      sellerTokenAccount,
+      ownerTokenAccount: treasuryTokenAccount,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    try {
+      await withdrawFunds();
+      assert.fail("proceeds released before any milestone was approved");
+    } catch (err) {
+      assert.include(err.toString(), "NoFundsToWithdraw");
+    }
+
+    try {
+      await program.methods.approveMilestone({ index: 0 }).accounts({
+        agreement: milestoneAgreement,
+        milestonePlan,
+        payer: arbiter.publicKey,
+      }).signers([arbiter]).rpc();
+      assert.fail("milestone approved by the wrong approver");
+    } catch (err) {
+      assert.include(err.toString(), "NotAuthorized");
+    }
+
+    await program.methods.approveMilestone({ index: 0 }).accounts({
+      agreement: milestoneAgreement,
+      milestonePlan,
+      payer: companyWallet.publicKey,
+    }).signers([companyWallet]).rpc();
+    await withdrawFunds();
+
+    let agreementAccount = await program.account.agreement.fetch(milestoneAgreement);
+    assert.equal(agreementAccount.approvedBps, 6000);
+    assert.equal(agreementAccount.fundsReleased.toNumber(), 360);
+    assert.isFalse(agreementAccount.fundsCommisionCollected);
+    let vaultBalance = await program.provider.connection.getTokenAccountBalance(seeds("payment"));
+    assert.equal(vaultBalance.value.amount, "240");
+
+    // Terminating the deal refunds the unapproved 40% to the investor
+    await program.methods.cancelAgreement({}).accounts({
+      settings,
+      agreement: milestoneAgreement,
+      projectTokenAccount: seeds("project"),
+      investor: null,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).rpc();
+
+    const investorTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      investor.publicKey
+    )).address;
+    const before = await program.provider.connection.getTokenAccountBalance(investorTokenAccount);
+    await program.methods.withdrawCancelledFunds({}).accounts({
+      agreement: milestoneAgreement,
+      investor: investorAccount,
+      agreementTokenAccount: seeds("payment"),
+      destinationTokenAccount: investorTokenAccount,
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      blockedWallet: blocklist(investor.publicKey),
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+    const after = await program.provider.connection.getTokenAccountBalance(investorTokenAccount);
+    assert.equal(Number(after.value.amount) - Number(before.value.amount), 240);
+
+    agreementAccount = await program.account.agreement.fetch(milestoneAgreement);
+    assert.isTrue(agreementAccount.agreementCancelled);
+    assert.isTrue(agreementAccount.terminated);
+    assert.equal(agreementAccount.tokensOwed.toNumber(), 60);
+
+    // The investor still claims the 60% of the tokens delivered against approved proceeds
+    const tokensBefore = await program.provider.connection.getTokenAccountBalance(investorTokenAccount);
+    await program.methods.withdrawTokens({}).accounts({
+      settings,
+      agreement: milestoneAgreement,
+      investor: investorAccount,
+      attestation: null,
+      blockedWallet: blocklist(investor.publicKey),
+      projectTokenAccount: seeds("project"),
+      investorTokenAccount,
+      referrerBlockedWallet: null,
+      referrerTokenAccount: null,
+      ownerTokenAccount: treasuryTokenAccount,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+    const tokensAfter = await program.provider.connection.getTokenAccountBalance(investorTokenAccount);
+    // 60 tokens less the 2.5% commission
+    assert.equal(Number(tokensAfter.value.amount) - Number(tokensBefore.value.amount), 59);
+
+    // The seller only reclaims the undelivered 40%
+    const sellerBefore = await program.provider.connection.getTokenAccountBalance(sellerTokenAccount);
+    await program.methods.withdrawCancelledFunds({}).accounts({
+      agreement: milestoneAgreement,
+      investor: null,
+      agreementTokenAccount: seeds("project"),
+      destinationTokenAccount: sellerTokenAccount,
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      blockedWallet: blocklist(tokenSeller.publicKey),
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+    const sellerAfter = await program.provider.connection.getTokenAccountBalance(sellerTokenAccount);
+    assert.equal(Number(sellerAfter.value.amount) - Number(sellerBefore.value.amount), 40);
+    vaultBalance = await program.provider.connection.getTokenAccountBalance(seeds("project"));
+    assert.equal(vaultBalance.value.amount, "0");
+  });
+
+  it("Rejects milestones once the terms are accepted", async () => {
+    const lateAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(lateAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const lateAgreement = seeds("agreement");
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      holdDuration: new anchor.BN(3600),
+    })).accounts({
+      settings,
+      agreement: lateAgreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(3600),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: lateAgreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    try {
+      await program.methods.setMilestones({
+        milestones: [{ basisPoints: 10000, approver: companyWallet.publicKey }],
+      }).accounts({
+        settings,
+        agreement: lateAgreement,
+        milestonePlan: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("milestones"), lateAgreement.toBuffer()],
+          program.programId
+        )[0],
+        operator: null,
+        proposal: null,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      }).rpc();
+      assert.fail("milestones changed after the seller accepted the terms");
+    } catch (err) {
+      assert.include(err.toString(), "AgreementAlreadyAccepted");
+    }
+  });
+});
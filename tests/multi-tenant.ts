@@ -0,0 +1,259 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createMint, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { defaultAgreementParams } from "./utils";
+
+describe("multi-tenant", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  const tenantId = new anchor.BN(Math.floor(Math.random() * 1_000_000) + 1);
+  const agreementId = new anchor.BN(0); // first id the tenant's counter assigns
+  const tenantSeed = Uint8Array.from(tenantId.toBuffer('le', 8));
+
+  const settings = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("settings"), tenantSeed],
+    program.programId
+  )[0];
+  const agreement = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("agreement"), tenantSeed, Uint8Array.from(agreementId.toBuffer('le', 8))],
+    program.programId
+  )[0];
+  const paymentTokenAccount = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("payment"), tenantSeed, Uint8Array.from(agreementId.toBuffer('le', 8))],
+    program.programId
+  )[0];
+  const projectTokenAccount = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("project"), tenantSeed, Uint8Array.from(agreementId.toBuffer('le', 8))],
+    program.programId
+  )[0];
+  const programData = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  )[0];
+
+  it("Rejects initialize from a non upgrade authority", async () => {
+    const frontRunner = anchor.web3.Keypair.generate();
+    const signature = await program.provider.connection.requestAirdrop(frontRunner.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    try {
+      await program.methods.initialize({
+        commissionBasisPoints: 10_000,
+        cancelTimeout: new anchor.BN(0),
+        owner: frontRunner.publicKey,
+        tenantId,
+        allowedMints: [],
+      }).accounts({
+        settings,
+        program: program.programId,
+        programData,
+        authority: frontRunner.publicKey,
+        payer: frontRunner.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      }).signers([frontRunner]).rpc();
+      assert.fail("settings were initialized by a non upgrade authority");
+    } catch (err) {
+      assert.include(err.toString(), "NotAuthorized");
+    }
+
+    const settingsAccount = await program.account.settings.fetchNullable(settings);
+    assert.isNull(settingsAccount);
+  });
+
+  it("Initializes a tenant", async () => {
+    const tx = await program.methods.initialize({
+      commissionBasisPoints: 250,
+      cancelTimeout: new anchor.BN(60),
+      owner: program.provider.wallet.publicKey,
+      tenantId,
+      allowedMints: [NATIVE_MINT],
+    }).accounts({
+      settings,
+      program: program.programId,
+      programData,
+      authority: program.provider.wallet.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+    console.log("Your transaction signature", tx);
+
+    const settingsAccount = await program.account.settings.fetch(settings);
+    assert.equal(settingsAccount.tenantId.toString(), tenantId.toString());
+    assert.equal(settingsAccount.commissionBasisPoints, 250);
+  });
+
+  it("Rejects a payment mint the tenant does not allow", async () => {
+    const otherMint = await createMint(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      program.provider.wallet.publicKey,
+      null,
+      6
+    );
+
+    try {
+      await program.methods.initializeAgreement(defaultAgreementParams()).accounts({
+        settings,
+        agreement,
+        paymentTokenMint: otherMint,
+        projectTokenMint: NATIVE_MINT,
+        paymentTokenAccount,
+        projectTokenAccount,
+        companyWallet: companyWallet.publicKey,
+        tokenSeller: tokenSeller.publicKey,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      }).rpc();
+      assert.fail("agreement with a disallowed mint was created");
+    } catch (err) {
+      assert.include(err.toString(), "PaymentMintNotAllowed");
+    }
+  });
+
+  it("Initializes an agreement namespaced by the tenant", async () => {
+    const tx = await program.methods.initializeAgreement(defaultAgreementParams()).accounts({
+      settings,
+      agreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount,
+      projectTokenAccount,
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+    console.log("Your transaction signature", tx);
+
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.equal(agreementAccount.tenantId.toString(), tenantId.toString());
+    assert.equal(agreementAccount.agreementId.toNumber(), agreementId.toNumber());
+    const settingsAccount = await program.account.settings.fetch(settings);
+    assert.equal(settingsAccount.nextAgreementId.toNumber(), agreementId.toNumber() + 1);
+  });
+
+  it("Rejects an explicit agreement id the counter has not passed", async () => {
+    const explicitId = new anchor.BN(1);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(explicitId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+
+    try {
+      await program.methods.initializeAgreement(defaultAgreementParams({
+        agreementId: explicitId,
+      })).accounts({
+        settings,
+        agreement: seeds("agreement"),
+        paymentTokenMint: NATIVE_MINT,
+        projectTokenMint: NATIVE_MINT,
+        paymentTokenAccount: seeds("payment"),
+        projectTokenAccount: seeds("project"),
+        companyWallet: companyWallet.publicKey,
+        tokenSeller: tokenSeller.publicKey,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      }).rpc();
+      assert.fail("agreement was created with an id the counter hands out");
+    } catch (err) {
+      assert.include(err.toString(), "AgreementIdNotReserved");
+    }
+
+    const settingsAccount = await program.account.settings.fetch(settings);
+    assert.equal(settingsAccount.nextAgreementId.toNumber(), 1);
+  });
+
+  it("Records the seller as depositor of project tokens", async () => {
+    const signature = await program.provider.connection.requestAirdrop(tokenSeller.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    const sellerTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      tokenSeller.publicKey
+    )).address;
+    const companyTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      companyWallet.publicKey
+    )).address;
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: sellerTokenAccount,
+        lamports: 100,
+      }),
+      createSyncNativeInstruction(sellerTokenAccount)
+    ));
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    const tx = await program.methods.depositProjectTokens({}).accounts({
+      agreement,
+      projectTokenAccount,
+      sellerTokenAccount,
+      companyTokenAccount,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+    console.log("Your transaction signature", tx);
+
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.isTrue(agreementAccount.sellerDeposited);
+    assert.equal(agreementAccount.tokenDepositor.toBase58(), tokenSeller.publicKey.toBase58());
+    assert.equal(agreementAccount.depositedTokens.toNumber(), 100);
+  });
+
+  it("Updates tenant settings", async () => {
+    const tx = await program.methods.updateSettings({
+      cancelTimeout: null,
+      commissionBasisPoints: 300,
+      owner: null,
+      allowedMints: [],
+      treasury: null,
+      multisigSigners: null,
+      multisigThreshold: null,
+      feeSplit: null,
+    }).accounts({
+      settings,
+      payer: program.provider.wallet.publicKey,
+    }).rpc();
+    console.log("Your transaction signature", tx);
+
+    const settingsAccount = await program.account.settings.fetch(settings);
+    assert.equal(settingsAccount.commissionBasisPoints, 300);
+    assert.equal(settingsAccount.allowedMints.length, 0);
+  });
+});
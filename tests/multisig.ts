@@ -0,0 +1,153 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { assert } from "chai";
+import { airdrop, initializeTenant } from "./utils";
+
+describe("multisig", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const cosigner = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, cosigner.publicKey);
+  });
+
+  it("Requires an approved proposal once multisig is enabled", async () => {
+    await program.methods.updateSettings({
+      cancelTimeout: null,
+      commissionBasisPoints: null,
+      owner: null,
+      allowedMints: null,
+      treasury: null,
+      multisigSigners: [program.provider.wallet.publicKey, cosigner.publicKey],
+      multisigThreshold: 2,
+      feeSplit: null,
+    }).accounts({
+      settings,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+    }).rpc();
+
+    const params = {
+      cancelTimeout: null,
+      commissionBasisPoints: 400,
+      owner: null,
+      allowedMints: null,
+      treasury: null,
+      multisigSigners: null,
+      multisigThreshold: null,
+      feeSplit: null,
+    };
+
+    try {
+      await program.methods.updateSettings(params).accounts({
+        settings,
+        proposal: null,
+        payer: program.provider.wallet.publicKey,
+      }).rpc();
+      assert.fail("settings were updated without a proposal");
+    } catch (err) {
+      assert.include(err.toString(), "ProposalRequired");
+    }
+
+    const settingsAccount = await program.account.settings.fetch(settings);
+    const proposal = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), settings.toBuffer(), Uint8Array.from(settingsAccount.nextProposalId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+
+    await program.methods.createProposal({
+      action: { updateSettings: { params } },
+      expiresAt: new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+    }).accounts({
+      settings,
+      proposal,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    try {
+      await program.methods.updateSettings(params).accounts({
+        settings,
+        proposal,
+        payer: program.provider.wallet.publicKey,
+      }).rpc();
+      assert.fail("settings were updated below the threshold");
+    } catch (err) {
+      assert.include(err.toString(), "ProposalThresholdNotMet");
+    }
+
+    await program.methods.approveProposal({}).accounts({
+      settings,
+      proposal,
+      payer: cosigner.publicKey,
+    }).signers([cosigner]).rpc();
+
+    await program.methods.updateSettings(params).accounts({
+      settings,
+      proposal,
+      payer: cosigner.publicKey,
+    }).signers([cosigner]).rpc();
+
+    const updatedSettings = await program.account.settings.fetch(settings);
+    assert.equal(updatedSettings.commissionBasisPoints, 400);
+    const proposalAccount = await program.account.proposal.fetch(proposal);
+    assert.isTrue(proposalAccount.executed);
+  });
+
+  it("Blocks a wallet only through an approved proposal", async () => {
+    const wallet = anchor.web3.Keypair.generate().publicKey;
+    const blockedWallet = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const blockWallet = (proposal: anchor.web3.PublicKey | null) => program.methods.blockWallet({ wallet }).accounts({
+      settings,
+      blockedWallet,
+      proposal,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    try {
+      await blockWallet(null);
+      assert.fail("wallet was blocked without a proposal");
+    } catch (err) {
+      assert.include(err.toString(), "ProposalRequired");
+    }
+
+    const settingsAccount = await program.account.settings.fetch(settings);
+    const proposal = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), settings.toBuffer(), Uint8Array.from(settingsAccount.nextProposalId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    await program.methods.createProposal({
+      action: { blockWallet: { params: { wallet } } },
+      expiresAt: new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+    }).accounts({
+      settings,
+      proposal,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+    await program.methods.approveProposal({}).accounts({
+      settings,
+      proposal,
+      payer: cosigner.publicKey,
+    }).signers([cosigner]).rpc();
+
+    await blockWallet(proposal);
+
+    const blockedWalletAccount = await program.account.blockedWallet.fetch(blockedWallet);
+    assert.equal(blockedWalletAccount.wallet.toBase58(), wallet.toBase58());
+    const proposalAccount = await program.account.proposal.fetch(proposal);
+    assert.isTrue(proposalAccount.executed);
+  });
+});
@@ -0,0 +1,321 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("native-sol", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+    program.programId
+  )[0];
+  const investorPda = (agreement: anchor.web3.PublicKey, wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("investor"), agreement.toBuffer(), wallet.toBuffer()],
+    program.programId
+  )[0];
+  const unwrapAccount = (agreement: anchor.web3.PublicKey, wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("unwrap"), agreement.toBuffer(), wallet.toBuffer()],
+    program.programId
+  )[0];
+  const balance = (wallet: anchor.web3.PublicKey) => program.provider.connection.getBalance(wallet);
+  const wrap = async (owner: anchor.web3.PublicKey, lamports: number) => {
+    const tokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      owner
+    )).address;
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: tokenAccount,
+        lamports,
+      }),
+      createSyncNativeInstruction(tokenAccount)
+    ));
+    return tokenAccount;
+  };
+  const fundedWallet = async () => {
+    const wallet = anchor.web3.Keypair.generate();
+    await airdrop(program, wallet.publicKey);
+    return wallet;
+  };
+
+  // Creates an accepted wrapped SOL agreement the seller has deposited its 100 tokens into
+  const createAgreement = async (terms: { publicSale?: any, commitDeadline?: number }) => {
+    const agreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const agreement = seeds("agreement");
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      publicSale: terms.publicSale ?? null,
+      commitDeadline: new anchor.BN(terms.commitDeadline ?? 0),
+    })).accounts({
+      settings,
+      agreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement,
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount: await wrap(tokenSeller.publicKey, 100),
+      companyTokenAccount: await wrap(companyWallet.publicKey, 0),
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    return { agreement, seeds };
+  };
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Sells public sale tokens for lamports", async () => {
+    const { agreement, seeds } = await createAgreement({
+      publicSale: {
+        tokenPrice: new anchor.BN(6_000_000_000),
+        minTicket: new anchor.BN(60),
+        maxTicket: new anchor.BN(600),
+        walletCap: new anchor.BN(0),
+      },
+    });
+    const buyer = await fundedWallet();
+
+    await program.methods.purchaseTokens({ amount: new anchor.BN(600) }).accounts({
+      agreement,
+      investor: investorPda(agreement, buyer.publicKey),
+      acknowledgement: null,
+      attestation: null,
+      blockedWallet: blocklist(buyer.publicKey),
+      paymentTokenAccount: seeds("payment"),
+      payerTokenAccount: null,
+      payer: buyer.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([buyer]).rpc();
+
+    const vault = await program.provider.connection.getTokenAccountBalance(seeds("payment"));
+    assert.equal(vault.value.amount, "600");
+    const investorAccount = await program.account.investor.fetch(investorPda(agreement, buyer.publicKey));
+    assert.equal(investorAccount.tokenAllocation.toNumber(), 100);
+
+    // Project tokens are paid out as tokens, even from a wrapped SOL vault
+    await new Promise(resolve => setTimeout(resolve, 2000));
+    const buyerTokenAccount = await wrap(buyer.publicKey, 0);
+    await program.methods.withdrawTokensBatch({}).accounts({
+      settings,
+      agreement,
+      projectTokenAccount: seeds("project"),
+      ownerTokenAccount: await wrap(program.provider.wallet.publicKey, 0),
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).remainingAccounts([
+      { pubkey: investorPda(agreement, buyer.publicKey), isWritable: true, isSigner: false },
+      { pubkey: buyerTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(buyer.publicKey), isWritable: false, isSigner: false },
+    ]).rpc();
+    // 100 tokens less the 2.5% commission
+    const tokens = await program.provider.connection.getTokenAccountBalance(buyerTokenAccount);
+    assert.equal(tokens.value.amount, "98");
+  });
+
+  it("Refunds a withdrawn commitment and releases an escrowed one in lamports", async () => {
+    const { agreement, seeds } = await createAgreement({});
+    const investors = [await fundedWallet(), await fundedWallet()];
+    await program.methods.addInvestors({
+      allocations: investors.map((investor) => (
+        { amount: new anchor.BN(300), tokenAmount: new anchor.BN(50), wallet: investor.publicKey, referral: null }
+      )),
+    }).accounts({
+      settings,
+      agreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts(investors.map((investor) => (
+      { pubkey: investorPda(agreement, investor.publicKey), isWritable: true, isSigner: false }
+    ))).rpc();
+
+    for (const investor of investors) {
+      await program.methods.depositStablecoins({ amount: new anchor.BN(300), memo: null, receiptNonce: null }).accounts({
+        agreement,
+        investor: investorPda(agreement, investor.publicKey),
+        acknowledgement: null,
+        attestation: null,
+        paymentReceipt: null,
+        paymentMint: null,
+        priceFeed: null,
+        blockedWallet: blocklist(investor.publicKey),
+        destinationTokenAccount: seeds("payment"),
+        payerTokenAccount: null,
+        payer: investor.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+      }).signers([investor]).rpc();
+    }
+
+    // The second investor is blocked, so its refund is escrowed
+    await program.methods.blockWallet({ wallet: investors[1].publicKey }).accounts({
+      settings,
+      blockedWallet: blocklist(investors[1].publicKey),
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    const withdrawCommitment = (investor: anchor.web3.Keypair) => program.methods.investorWithdrawCommitment({}).accounts({
+      agreement,
+      investor: investorPda(agreement, investor.publicKey),
+      referrer: null,
+      blockedWallet: blocklist(investor.publicKey),
+      paymentTokenAccount: seeds("payment"),
+      investorTokenAccount: null,
+      unwrapTokenAccount: unwrapAccount(agreement, investor.publicKey),
+      nativeMint: NATIVE_MINT,
+      destination: investor.publicKey,
+      rentPayer: program.provider.wallet.publicKey,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).signers([investor]).rpc();
+
+    // Lamports sent to the unwrap account ahead of time must not block the refund
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: unwrapAccount(agreement, investors[0].publicKey),
+        lamports: 1,
+      })
+    ));
+    await withdrawCommitment(investors[0]);
+    let vault = await program.provider.connection.getTokenAccountBalance(seeds("payment"));
+    assert.equal(vault.value.amount, "300");
+    assert.isNull(await program.provider.connection.getAccountInfo(unwrapAccount(agreement, investors[0].publicKey)));
+
+    await withdrawCommitment(investors[1]);
+    vault = await program.provider.connection.getTokenAccountBalance(seeds("payment"));
+    assert.equal(vault.value.amount, "300");
+
+    await program.methods.unblockWallet({ wallet: investors[1].publicKey }).accounts({
+      settings,
+      blockedWallet: blocklist(investors[1].publicKey),
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+    }).rpc();
+
+    // Paid by the operator, the wallet receives exactly the escrowed refund and no rent
+    const before = await balance(investors[1].publicKey);
+    await program.methods.releaseEscrow({}).accounts({
+      settings,
+      agreement,
+      investor: investorPda(agreement, investors[1].publicKey),
+      blockedWallet: blocklist(investors[1].publicKey),
+      agreementTokenAccount: seeds("payment"),
+      destinationTokenAccount: null,
+      unwrapTokenAccount: unwrapAccount(agreement, investors[1].publicKey),
+      nativeMint: NATIVE_MINT,
+      destination: investors[1].publicKey,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).rpc();
+    assert.equal(await balance(investors[1].publicKey) - before, 300);
+    vault = await program.provider.connection.getTokenAccountBalance(seeds("payment"));
+    assert.equal(vault.value.amount, "0");
+  });
+
+  it("Refunds the excess of an oversubscribed round in lamports", async () => {
+    const commitDeadline = Math.floor(Date.now() / 1000) + 5;
+    const { agreement, seeds } = await createAgreement({ commitDeadline });
+    const committers = [await fundedWallet(), await fundedWallet()];
+
+    for (const [index, amount] of [600, 300].entries()) {
+      await program.methods.commitFunds({ amount: new anchor.BN(amount) }).accounts({
+        agreement,
+        investor: investorPda(agreement, committers[index].publicKey),
+        acknowledgement: null,
+        attestation: null,
+        blockedWallet: blocklist(committers[index].publicKey),
+        paymentTokenAccount: seeds("payment"),
+        payerTokenAccount: await wrap(committers[index].publicKey, amount),
+        payer: committers[index].publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+      }).signers([committers[index]]).rpc();
+    }
+
+    await new Promise(resolve => setTimeout(resolve, (commitDeadline + 2) * 1000 - Date.now()));
+
+    const before = await Promise.all(committers.map((committer) => balance(committer.publicKey)));
+    await program.methods.settleCommitments({}).accounts({
+      agreement,
+      paymentTokenAccount: seeds("payment"),
+      nativeMint: NATIVE_MINT,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).remainingAccounts(committers.flatMap((committer) => [
+      { pubkey: investorPda(agreement, committer.publicKey), isWritable: true, isSigner: false },
+      { pubkey: committer.publicKey, isWritable: true, isSigner: false },
+      { pubkey: blocklist(committer.publicKey), isWritable: false, isSigner: false },
+      { pubkey: unwrapAccount(agreement, committer.publicKey), isWritable: true, isSigner: false },
+    ])).rpc();
+
+    assert.equal(await balance(committers[0].publicKey) - before[0], 200);
+    assert.equal(await balance(committers[1].publicKey) - before[1], 100);
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.ok(agreementAccount.holdDurationStart.toNumber() > 0);
+  });
+});
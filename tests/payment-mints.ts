@@ -0,0 +1,453 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { MockPriceFeed } from "../target/types/mock_price_feed";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createMint, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount, mintTo } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("payment-mints", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+  const priceFeedProgram = anchor.workspace.mockPriceFeed as Program<MockPriceFeed>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Accepts deposits in a second payment mint", async () => {
+    const multiMintAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(multiMintAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const multiMintAgreement = seeds("agreement");
+    const investor = anchor.web3.Keypair.generate();
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), multiMintAgreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const signature = await program.provider.connection.requestAirdrop(investor.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    const secondMint = await createMint(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      program.provider.wallet.publicKey,
+      null,
+      9
+    );
+    const paymentMint = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("payment_mint"), multiMintAgreement.toBuffer(), secondMint.toBuffer()],
+      program.programId
+    )[0];
+    const secondVault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("payment"), tenantSeed, Uint8Array.from(multiMintAgreementId.toBuffer('le', 8)), secondMint.toBuffer()],
+      program.programId
+    )[0];
+
+    await program.methods.initializeAgreement(defaultAgreementParams()).accounts({
+      settings,
+      agreement: multiMintAgreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    // Two payment mint units per unit of the second mint
+    await program.methods.addPaymentMint({ rate: new anchor.BN(2_000_000_000), oracle: null }).accounts({
+      settings,
+      agreement: multiMintAgreement,
+      paymentMint,
+      mint: secondMint,
+      paymentTokenAccount: secondVault,
+      priceFeed: null,
+      operator: null,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).rpc();
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(600), tokenAmount: new anchor.BN(100), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement: multiMintAgreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: multiMintAgreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    const lateMint = await createMint(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      program.provider.wallet.publicKey,
+      null,
+      9
+    );
+    try {
+      await program.methods.addPaymentMint({ rate: new anchor.BN(1_000_000_000), oracle: null }).accounts({
+        settings,
+        agreement: multiMintAgreement,
+        paymentMint: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("payment_mint"), multiMintAgreement.toBuffer(), lateMint.toBuffer()],
+          program.programId
+        )[0],
+        mint: lateMint,
+        paymentTokenAccount: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("payment"), tenantSeed, Uint8Array.from(multiMintAgreementId.toBuffer('le', 8)), lateMint.toBuffer()],
+          program.programId
+        )[0],
+        priceFeed: null,
+        operator: null,
+        proposal: null,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      }).rpc();
+      assert.fail("payment mint was added after the seller accepted");
+    } catch (err) {
+      assert.include(err.toString(), "AgreementAlreadyAccepted");
+    }
+
+    const sellerTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      tokenSeller.publicKey
+    )).address;
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: sellerTokenAccount,
+        lamports: 100,
+      }),
+      createSyncNativeInstruction(sellerTokenAccount)
+    ));
+    const companyTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      companyWallet.publicKey
+    )).address;
+    const investorTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      secondMint,
+      investor.publicKey
+    )).address;
+    await mintTo(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      secondMint,
+      investorTokenAccount,
+      program.provider.wallet.publicKey,
+      300
+    );
+
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement: multiMintAgreement,
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount,
+      companyTokenAccount,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    await program.methods.depositStablecoins({ amount: new anchor.BN(300), memo: null, receiptNonce: null }).accounts({
+      agreement: multiMintAgreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint,
+      priceFeed: null,
+      blockedWallet: blocklist(investor.publicKey),
+      destinationTokenAccount: secondVault,
+      payerTokenAccount: investorTokenAccount,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    const agreementAccount = await program.account.agreement.fetch(multiMintAgreement);
+    assert.equal(agreementAccount.totalInvested.toNumber(), 600);
+    assert.equal(agreementAccount.secondaryInvested.toNumber(), 600);
+    assert.ok(agreementAccount.holdDurationStart.toNumber() > 0);
+
+    const sellerSecondAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      secondMint,
+      tokenSeller.publicKey
+    )).address;
+    const treasurySecondAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      secondMint,
+      program.provider.wallet.publicKey
+    )).address;
+    const tx = await program.methods.withdrawFunds({}).accounts({
+      settings,
+      agreement: multiMintAgreement,
+      paymentMint,
+      paymentTokenAccount: secondVault,
+      blockedWallet: blocklist(tokenSeller.publicKey),
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      tokenSeller: null,
+      sellerTokenAccount: sellerSecondAccount,
+      ownerTokenAccount: treasurySecondAccount,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+    console.log("Your transaction signature", tx);
+
+    const sellerBalance = await program.provider.connection.getTokenAccountBalance(sellerSecondAccount);
+    const treasuryBalance = await program.provider.connection.getTokenAccountBalance(treasurySecondAccount);
+    assert.equal(Number(sellerBalance.value.amount) + Number(treasuryBalance.value.amount), 300);
+    // The commission is recorded against the mint it was paid in
+    const paymentMintAccount = await program.account.paymentMint.fetch(paymentMint);
+    assert.ok(paymentMintAccount.fundsWithdrawn);
+    assert.equal(paymentMintAccount.commissionPaid.toNumber(), 7);
+  });
+
+  it("Prices deposits in a volatile mint with an oracle", async () => {
+    const oracleAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(oracleAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const oracleAgreement = seeds("agreement");
+    const investor = anchor.web3.Keypair.generate();
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), oracleAgreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const signature = await program.provider.connection.requestAirdrop(investor.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    const volatileMint = await createMint(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      program.provider.wallet.publicKey,
+      null,
+      9
+    );
+    const paymentMint = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("payment_mint"), oracleAgreement.toBuffer(), volatileMint.toBuffer()],
+      program.programId
+    )[0];
+    const volatileVault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("payment"), tenantSeed, Uint8Array.from(oracleAgreementId.toBuffer('le', 8)), volatileMint.toBuffer()],
+      program.programId
+    )[0];
+
+    // 2.5 payment mint units per unit of the volatile mint, last published an hour ago
+    const priceFeed = anchor.web3.Keypair.generate();
+    const now = Math.floor(Date.now() / 1000);
+    await priceFeedProgram.methods.initialize({
+      price: new anchor.BN(2_500_000_000),
+      confidence: new anchor.BN(10_000_000),
+      publishTime: new anchor.BN(now - 3600),
+    }).accounts({
+      priceFeed: priceFeed.publicKey,
+      payer: program.provider.wallet.publicKey,
+    }).signers([priceFeed]).rpc();
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      expectedPayment: new anchor.BN(500),
+    })).accounts({
+      settings,
+      agreement: oracleAgreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.addPaymentMint({
+      rate: new anchor.BN(0),
+      oracle: { maxPriceAge: new anchor.BN(300), maxConfidenceBps: 100 },
+    }).accounts({
+      settings,
+      agreement: oracleAgreement,
+      paymentMint,
+      mint: volatileMint,
+      paymentTokenAccount: volatileVault,
+      priceFeed: priceFeed.publicKey,
+      operator: null,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).rpc();
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(500), tokenAmount: new anchor.BN(100), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement: oracleAgreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(500),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: oracleAgreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    const sellerTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      tokenSeller.publicKey
+    )).address;
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: sellerTokenAccount,
+        lamports: 100,
+      }),
+      createSyncNativeInstruction(sellerTokenAccount)
+    ));
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement: oracleAgreement,
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount,
+      companyTokenAccount: (await getOrCreateAssociatedTokenAccount(
+        program.provider.connection,
+        program.provider.wallet.payer,
+        NATIVE_MINT,
+        companyWallet.publicKey
+      )).address,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    const investorTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      volatileMint,
+      investor.publicKey
+    )).address;
+    await mintTo(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      volatileMint,
+      investorTokenAccount,
+      program.provider.wallet.publicKey,
+      250
+    );
+
+    // Investor accepts paying up to 210 units for the 500 allocation
+    const deposit = () => program.methods.depositStablecoins({ amount: new anchor.BN(210), memo: null, receiptNonce: null }).accounts({
+      agreement: oracleAgreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint,
+      priceFeed: priceFeed.publicKey,
+      blockedWallet: anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("blocklist"), tenantSeed, investor.publicKey.toBuffer()],
+        program.programId
+      )[0],
+      destinationTokenAccount: volatileVault,
+      payerTokenAccount: investorTokenAccount,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    try {
+      await deposit();
+      assert.fail("stale price accepted");
+    } catch (err) {
+      assert.include(err.toString(), "StalePrice");
+    }
+
+    await priceFeedProgram.methods.setPrice({
+      price: new anchor.BN(2_500_000_000),
+      confidence: new anchor.BN(10_000_000),
+      publishTime: new anchor.BN(now),
+    }).accounts({
+      priceFeed: priceFeed.publicKey,
+      authority: program.provider.wallet.publicKey,
+    }).rpc();
+    await deposit();
+
+    const investorState = await program.account.investor.fetch(investorAccount);
+    assert.equal(investorState.paidAmount.toNumber(), 200);
+    assert.equal(investorState.paidRate.toNumber(), 2_500_000_000);
+    const vaultBalance = await program.provider.connection.getTokenAccountBalance(volatileVault);
+    assert.equal(vaultBalance.value.amount, "200");
+    const agreementAccount = await program.account.agreement.fetch(oracleAgreement);
+    assert.equal(agreementAccount.totalInvested.toNumber(), 500);
+    assert.ok(agreementAccount.holdDurationStart.toNumber() > 0);
+  });
+});
@@ -0,0 +1,190 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("pro-rata", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Settles an oversubscribed round pro rata", async () => {
+    const proRataAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(proRataAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const proRataAgreement = seeds("agreement");
+    const investorPda = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), proRataAgreement.toBuffer(), wallet.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const committers = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const committer of committers) {
+      const signature = await program.provider.connection.requestAirdrop(committer.publicKey, 1000000000);
+      await program.provider.connection.confirmTransaction(signature);
+    }
+    const commitDeadline = Math.floor(Date.now() / 1000) + 5;
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      commitDeadline: new anchor.BN(commitDeadline),
+    })).accounts({
+      settings,
+      agreement: proRataAgreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: proRataAgreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    const wrap = async (owner: anchor.web3.PublicKey, lamports: number) => {
+      const tokenAccount = (await getOrCreateAssociatedTokenAccount(
+        program.provider.connection,
+        program.provider.wallet.payer,
+        NATIVE_MINT,
+        owner
+      )).address;
+      await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: program.provider.wallet.publicKey,
+          toPubkey: tokenAccount,
+          lamports,
+        }),
+        createSyncNativeInstruction(tokenAccount)
+      ));
+      return tokenAccount;
+    };
+    const sellerTokenAccount = await wrap(tokenSeller.publicKey, 100);
+    const companyTokenAccount = await wrap(companyWallet.publicKey, 0);
+    const committerTokenAccounts = [await wrap(committers[0].publicKey, 600), await wrap(committers[1].publicKey, 300)];
+
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement: proRataAgreement,
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount,
+      companyTokenAccount,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    for (const [index, amount] of [600, 300].entries()) {
+      await program.methods.commitFunds({ amount: new anchor.BN(amount) }).accounts({
+        agreement: proRataAgreement,
+        investor: investorPda(committers[index].publicKey),
+        acknowledgement: null,
+        attestation: null,
+        blockedWallet: blocklist(committers[index].publicKey),
+        paymentTokenAccount: seeds("payment"),
+        payerTokenAccount: committerTokenAccounts[index],
+        payer: committers[index].publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+      }).signers([committers[index]]).rpc();
+    }
+
+    // The first committer's excess is escrowed while blocked
+    await program.methods.blockWallet({ wallet: committers[0].publicKey }).accounts({
+      settings,
+      blockedWallet: blocklist(committers[0].publicKey),
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    await new Promise(resolve => setTimeout(resolve, (commitDeadline + 2) * 1000 - Date.now()));
+
+    const tx = await program.methods.settleCommitments({}).accounts({
+      agreement: proRataAgreement,
+      paymentTokenAccount: seeds("payment"),
+      nativeMint: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).remainingAccounts(committers.flatMap((committer, index) => [
+      { pubkey: investorPda(committer.publicKey), isWritable: true, isSigner: false },
+      { pubkey: committerTokenAccounts[index], isWritable: true, isSigner: false },
+      { pubkey: blocklist(committer.publicKey), isWritable: false, isSigner: false },
+    ])).rpc();
+    console.log("Your transaction signature", tx);
+
+    const investorAccount = await program.account.investor.fetch(investorPda(committers[0].publicKey));
+    assert.equal(investorAccount.requiredAmount.toNumber(), 400);
+    assert.equal(investorAccount.tokenAllocation.toNumber(), 66);
+    assert.equal(investorAccount.escrowedRefund.toNumber(), 200);
+    const refund = await program.provider.connection.getTokenAccountBalance(committerTokenAccounts[1]);
+    assert.equal(refund.value.amount, "100");
+
+    const agreementAccount = await program.account.agreement.fetch(proRataAgreement);
+    assert.equal(agreementAccount.totalInvested.toNumber(), 600);
+    assert.equal(agreementAccount.tokensSold.toNumber(), 99);
+    assert.ok(agreementAccount.holdDurationStart.toNumber() > 0);
+
+    await program.methods.unblockWallet({ wallet: committers[0].publicKey }).accounts({
+      settings,
+      blockedWallet: blocklist(committers[0].publicKey),
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+    }).rpc();
+    await program.methods.releaseEscrow({}).accounts({
+      settings,
+      agreement: proRataAgreement,
+      investor: investorPda(committers[0].publicKey),
+      blockedWallet: blocklist(committers[0].publicKey),
+      agreementTokenAccount: seeds("payment"),
+      destinationTokenAccount: committerTokenAccounts[0],
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).rpc();
+    const released = await program.provider.connection.getTokenAccountBalance(committerTokenAccounts[0]);
+    assert.equal(released.value.amount, "200");
+  });
+});
@@ -0,0 +1,329 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("public-sale", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Sells tokens to any wallet in a public sale", async () => {
+    const publicAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(publicAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const publicAgreement = seeds("agreement");
+    const investorPda = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), publicAgreement.toBuffer(), wallet.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const buyers = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const buyer of buyers) {
+      const signature = await program.provider.connection.requestAirdrop(buyer.publicKey, 1000000000);
+      await program.provider.connection.confirmTransaction(signature);
+    }
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      publicSale: {
+        tokenPrice: new anchor.BN(6_000_000_000),
+        minTicket: new anchor.BN(60),
+        maxTicket: new anchor.BN(300),
+        walletCap: new anchor.BN(360),
+      },
+    })).accounts({
+      settings,
+      agreement: publicAgreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: publicAgreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    const wrap = async (owner: anchor.web3.PublicKey, lamports: number) => {
+      const tokenAccount = (await getOrCreateAssociatedTokenAccount(
+        program.provider.connection,
+        program.provider.wallet.payer,
+        NATIVE_MINT,
+        owner
+      )).address;
+      await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: program.provider.wallet.publicKey,
+          toPubkey: tokenAccount,
+          lamports,
+        }),
+        createSyncNativeInstruction(tokenAccount)
+      ));
+      return tokenAccount;
+    };
+    const sellerTokenAccount = await wrap(tokenSeller.publicKey, 100);
+    const companyTokenAccount = await wrap(companyWallet.publicKey, 0);
+    const buyerTokenAccounts = [await wrap(buyers[0].publicKey, 600), await wrap(buyers[1].publicKey, 600)];
+
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement: publicAgreement,
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount,
+      companyTokenAccount,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    const purchase = (index: number, amount: number) => program.methods.purchaseTokens({
+      amount: new anchor.BN(amount),
+    }).accounts({
+      agreement: publicAgreement,
+      investor: investorPda(buyers[index].publicKey),
+      acknowledgement: null,
+      attestation: null,
+      blockedWallet: blocklist(buyers[index].publicKey),
+      paymentTokenAccount: seeds("payment"),
+      payerTokenAccount: buyerTokenAccounts[index],
+      payer: buyers[index].publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([buyers[index]]).rpc();
+
+    try {
+      await purchase(0, 30);
+      assert.fail("Ticket below the minimum should be rejected");
+    } catch (e) {
+      assert.include(e.toString(), "TicketBelowMinimum");
+    }
+
+    await purchase(0, 300);
+
+    try {
+      await purchase(0, 120);
+      assert.fail("Purchase above the wallet cap should be rejected");
+    } catch (e) {
+      assert.include(e.toString(), "WalletCapExceeded");
+    }
+
+    await purchase(0, 60);
+    const investorAccount = await program.account.investor.fetch(investorPda(buyers[0].publicKey));
+    assert.equal(investorAccount.requiredAmount.toNumber(), 360);
+    assert.equal(investorAccount.tokenAllocation.toNumber(), 60);
+    assert.ok(investorAccount.investorDeposited);
+
+    // A buyer backing out before the sale completes gets the rent it paid back
+    await purchase(1, 60);
+    const withdrawCommitment = (rentPayer: anchor.web3.PublicKey) => program.methods.investorWithdrawCommitment({}).accounts({
+      agreement: publicAgreement,
+      investor: investorPda(buyers[1].publicKey),
+      referrer: null,
+      blockedWallet: blocklist(buyers[1].publicKey),
+      paymentTokenAccount: seeds("payment"),
+      investorTokenAccount: buyerTokenAccounts[1],
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      rentPayer,
+      payer: buyers[1].publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).signers([buyers[1]]).rpc();
+    try {
+      await withdrawCommitment(program.provider.wallet.publicKey);
+      assert.fail("Rent of a buyer's investor account returned to the agreement owner");
+    } catch (e) {
+      assert.include(e.toString(), "InvalidDestination");
+    }
+    const rent = (await program.provider.connection.getAccountInfo(investorPda(buyers[1].publicKey))).lamports;
+    const lamportsBefore = await program.provider.connection.getBalance(buyers[1].publicKey);
+    await withdrawCommitment(buyers[1].publicKey);
+    assert.isNull(await program.provider.connection.getAccountInfo(investorPda(buyers[1].publicKey)));
+    assert.equal(await program.provider.connection.getBalance(buyers[1].publicKey), lamportsBefore + rent);
+
+    const tx = await purchase(1, 240);
+    console.log("Your transaction signature", tx);
+
+    const agreementAccount = await program.account.agreement.fetch(publicAgreement);
+    assert.equal(agreementAccount.investorsCount.toNumber(), 2);
+    assert.equal(agreementAccount.totalInvested.toNumber(), 600);
+    assert.equal(agreementAccount.tokensSold.toNumber(), 100);
+    assert.ok(agreementAccount.holdDurationStart.toNumber() > 0);
+  });
+
+  it("Completes the sale below the minimum ticket and returns the rounding dust to the seller", async () => {
+    const agreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const buyers = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const buyer of buyers) {
+      await airdrop(program, buyer.publicKey);
+    }
+
+    const initialize = (tokenPrice: number) => program.methods.initializeAgreement(defaultAgreementParams({
+      publicSale: {
+        tokenPrice: new anchor.BN(tokenPrice),
+        minTicket: new anchor.BN(60),
+        maxTicket: new anchor.BN(0),
+        walletCap: new anchor.BN(0),
+      },
+    })).accounts({
+      settings,
+      agreement: seeds("agreement"),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    // 100 tokens at 5 per token raise 500, not the expected 600
+    try {
+      await initialize(5_000_000_000);
+      assert.fail("Price not matching the expected payment should be rejected");
+    } catch (e) {
+      assert.include(e.toString(), "InvalidPublicSaleTerms");
+    }
+    await initialize(6_000_000_000);
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: seeds("agreement"),
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    const sellerTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      tokenSeller.publicKey
+    )).address;
+    const companyTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      companyWallet.publicKey
+    )).address;
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: sellerTokenAccount,
+        lamports: 100,
+      }),
+      createSyncNativeInstruction(sellerTokenAccount)
+    ));
+    const sellerBalance = Number((await program.provider.connection.getTokenAccountBalance(sellerTokenAccount)).value.amount);
+
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement: seeds("agreement"),
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount,
+      companyTokenAccount,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    const purchase = (buyer: anchor.web3.Keypair, amount: number) => program.methods.purchaseTokens({
+      amount: new anchor.BN(amount),
+    }).accounts({
+      agreement: seeds("agreement"),
+      investor: anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("investor"), seeds("agreement").toBuffer(), buyer.publicKey.toBuffer()],
+        program.programId
+      )[0],
+      acknowledgement: null,
+      attestation: null,
+      blockedWallet: blocklist(buyer.publicKey),
+      paymentTokenAccount: seeds("payment"),
+      payerTokenAccount: null,
+      payer: buyer.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([buyer]).rpc();
+
+    // 599 buys 99 tokens, 5 of the payment pays for a token fraction
+    await purchase(buyers[0], 599);
+    await purchase(buyers[1], 1);
+
+    const agreementAccount = await program.account.agreement.fetch(seeds("agreement"));
+    assert.equal(agreementAccount.totalInvested.toNumber(), 600);
+    assert.equal(agreementAccount.tokensSold.toNumber(), 99);
+    assert.ok(agreementAccount.holdDurationStart.toNumber() > 0);
+
+    await program.methods.withdrawUnsoldTokens({}).accounts({
+      agreement: seeds("agreement"),
+      projectTokenAccount: seeds("project"),
+      blockedWallet: blocklist(tokenSeller.publicKey),
+      sellerTokenAccount,
+      payer: tokenSeller.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).signers([tokenSeller]).rpc();
+
+    const sellerBalanceAfter = Number((await program.provider.connection.getTokenAccountBalance(sellerTokenAccount)).value.amount);
+    assert.equal(sellerBalanceAfter - sellerBalance, -99);
+  });
+});
@@ -0,0 +1,374 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("referrals", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Pays referrers a share of the token commission", async () => {
+    const referralAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(referralAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const referralAgreement = seeds("agreement");
+    const referrer = anchor.web3.Keypair.generate();
+    const investor = anchor.web3.Keypair.generate();
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), referralAgreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const referrerAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("referrer"), tenantSeed, referrer.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const signature = await program.provider.connection.requestAirdrop(investor.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      expectedTokens: new anchor.BN(1000),
+    })).accounts({
+      settings,
+      agreement: referralAgreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.addInvestors({
+      allocations: [{
+        amount: new anchor.BN(600),
+        tokenAmount: new anchor.BN(1000),
+        wallet: investor.publicKey,
+        referral: { referrer: referrer.publicKey, basisPoints: 5000 },
+      }],
+    }).accounts({
+      settings,
+      agreement: referralAgreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([
+      { pubkey: investorAccount, isWritable: true, isSigner: false },
+      { pubkey: referrerAccount, isWritable: true, isSigner: false },
+    ]).rpc();
+
+    let referrerState = await program.account.referrer.fetch(referrerAccount);
+    assert.equal(referrerState.wallet.toBase58(), referrer.publicKey.toBase58());
+    assert.equal(referrerState.referredCount.toNumber(), 1);
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(1000),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: referralAgreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    const tokenAccount = async (wallet: anchor.web3.PublicKey) => (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      wallet
+    )).address;
+    const sellerTokenAccount = await tokenAccount(tokenSeller.publicKey);
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: sellerTokenAccount,
+        lamports: 1000,
+      }),
+      createSyncNativeInstruction(sellerTokenAccount)
+    ));
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement: referralAgreement,
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount,
+      companyTokenAccount: await tokenAccount(companyWallet.publicKey),
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    await program.methods.depositStablecoins({ amount: new anchor.BN(600), memo: null, receiptNonce: null }).accounts({
+      agreement: referralAgreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet: blocklist(investor.publicKey),
+      destinationTokenAccount: seeds("payment"),
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    const investorTokenAccount = await tokenAccount(investor.publicKey);
+    const referrerTokenAccount = await tokenAccount(referrer.publicKey);
+    const withdrawTokens = (referred: boolean) => program.methods.withdrawTokens({}).accounts({
+      settings,
+      agreement: referralAgreement,
+      investor: investorAccount,
+      attestation: null,
+      blockedWallet: blocklist(investor.publicKey),
+      projectTokenAccount: seeds("project"),
+      investorTokenAccount,
+      referrerBlockedWallet: referred ? blocklist(referrer.publicKey) : null,
+      referrerTokenAccount: referred ? referrerTokenAccount : null,
+      ownerTokenAccount: await tokenAccount(program.provider.wallet.publicKey),
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    try {
+      await withdrawTokens(false);
+      assert.fail("referred claim paid without the referrer");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidReferrerAccount");
+    }
+    const investorBefore = await program.provider.connection.getTokenAccountBalance(investorTokenAccount);
+    await withdrawTokens(true);
+
+    // 2.5% of 1000 is 25, half of which goes to the referrer
+    const investorAfter = await program.provider.connection.getTokenAccountBalance(investorTokenAccount);
+    const referrerBalance = await program.provider.connection.getTokenAccountBalance(referrerTokenAccount);
+    assert.equal(Number(investorAfter.value.amount) - Number(investorBefore.value.amount), 975);
+    assert.equal(referrerBalance.value.amount, "12");
+
+    const agreementAccount = await program.account.agreement.fetch(referralAgreement);
+    assert.equal(agreementAccount.referralFeesPaid.toNumber(), 12);
+    assert.equal(agreementAccount.tokenCommisionCollected.toNumber(), 25);
+  });
+
+  it("Pays referral fees in a batch and releases the referral of a withdrawn commitment", async () => {
+    const agreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const agreement = seeds("agreement");
+    const referrer = anchor.web3.Keypair.generate();
+    const referrerAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("referrer"), tenantSeed, referrer.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const investorPda = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), agreement.toBuffer(), wallet.toBuffer()],
+      program.programId
+    )[0];
+    const tokenAccount = async (wallet: anchor.web3.PublicKey) => (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      wallet
+    )).address;
+    // Two referred investors, one of whom backs out before paying and is replaced by an unreferred one
+    const [referred, withdrawn, replacement] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const investor of [referred, withdrawn, replacement]) {
+      await airdrop(program, investor.publicKey);
+    }
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      expectedTokens: new anchor.BN(1000),
+    })).accounts({
+      settings,
+      agreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    // Lamports sent to the referrer PDA ahead of time must not block its registration
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: referrerAccount,
+        lamports: 1,
+      })
+    ));
+
+    const addInvestors = (investors: anchor.web3.Keypair[], referral: boolean) => program.methods.addInvestors({
+      allocations: investors.map((investor) => ({
+        amount: new anchor.BN(300),
+        tokenAmount: new anchor.BN(500),
+        wallet: investor.publicKey,
+        referral: referral ? { referrer: referrer.publicKey, basisPoints: 5000 } : null,
+      })),
+    }).accounts({
+      settings,
+      agreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([
+      ...investors.map((investor) => ({ pubkey: investorPda(investor.publicKey), isWritable: true, isSigner: false })),
+      ...(referral ? investors.map(() => ({ pubkey: referrerAccount, isWritable: true, isSigner: false })) : []),
+    ]).rpc();
+    await addInvestors([referred, withdrawn], true);
+    assert.equal((await program.account.referrer.fetch(referrerAccount)).referredCount.toNumber(), 2);
+
+    await program.methods.investorWithdrawCommitment({}).accounts({
+      agreement,
+      investor: investorPda(withdrawn.publicKey),
+      referrer: referrerAccount,
+      blockedWallet: blocklist(withdrawn.publicKey),
+      paymentTokenAccount: seeds("payment"),
+      investorTokenAccount: null,
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      rentPayer: program.provider.wallet.publicKey,
+      payer: withdrawn.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).signers([withdrawn]).rpc();
+    assert.equal((await program.account.referrer.fetch(referrerAccount)).referredCount.toNumber(), 1);
+
+    await addInvestors([replacement], false);
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(1000),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    const sellerTokenAccount = await tokenAccount(tokenSeller.publicKey);
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: sellerTokenAccount,
+        lamports: 1000,
+      }),
+      createSyncNativeInstruction(sellerTokenAccount)
+    ));
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement,
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount,
+      companyTokenAccount: await tokenAccount(companyWallet.publicKey),
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    const deposit = (investor: anchor.web3.Keypair) => program.methods.depositStablecoins({ amount: new anchor.BN(300), memo: null, receiptNonce: null }).accounts({
+      agreement,
+      investor: investorPda(investor.publicKey),
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet: blocklist(investor.publicKey),
+      destinationTokenAccount: seeds("payment"),
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+    await deposit(referred);
+    await deposit(replacement);
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    const referredTokenAccount = await tokenAccount(referred.publicKey);
+    const replacementTokenAccount = await tokenAccount(replacement.publicKey);
+    const referrerTokenAccount = await tokenAccount(referrer.publicKey);
+    const referrerBefore = Number((await program.provider.connection.getTokenAccountBalance(referrerTokenAccount)).value.amount);
+    await program.methods.withdrawTokensBatch({}).accounts({
+      settings,
+      agreement,
+      projectTokenAccount: seeds("project"),
+      ownerTokenAccount: await tokenAccount(program.provider.wallet.publicKey),
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).remainingAccounts([
+      { pubkey: investorPda(referred.publicKey), isWritable: true, isSigner: false },
+      { pubkey: referredTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(referred.publicKey), isWritable: false, isSigner: false },
+      { pubkey: referrerTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(referrer.publicKey), isWritable: false, isSigner: false },
+      { pubkey: investorPda(replacement.publicKey), isWritable: true, isSigner: false },
+      { pubkey: replacementTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(replacement.publicKey), isWritable: false, isSigner: false },
+    ]).rpc();
+
+    // 2.5% of 500 is 12, half of which goes to the referrer
+    const referrerAfter = Number((await program.provider.connection.getTokenAccountBalance(referrerTokenAccount)).value.amount);
+    assert.equal(referrerAfter - referrerBefore, 6);
+    assert.isTrue((await program.account.investor.fetch(investorPda(referred.publicKey))).hasWithdrawnTokens);
+    assert.isTrue((await program.account.investor.fetch(investorPda(replacement.publicKey))).hasWithdrawnTokens);
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.equal(agreementAccount.referralFeesPaid.toNumber(), 6);
+    assert.equal(agreementAccount.tokenCommisionCollected.toNumber(), 24);
+  });
+});
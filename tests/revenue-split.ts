@@ -0,0 +1,320 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("revenue-split", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Splits seller proceeds across revenue share recipients", async () => {
+    const splitAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(splitAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const splitAgreement = seeds("agreement");
+    const advisor = anchor.web3.Keypair.generate();
+    const investor = anchor.web3.Keypair.generate();
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), splitAgreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const signature = await program.provider.connection.requestAirdrop(investor.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    const initializeAgreement = (revenueSplit: { recipient: anchor.web3.PublicKey, basisPoints: number }[]) =>
+      program.methods.initializeAgreement(defaultAgreementParams({
+        revenueSplit,
+      })).accounts({
+        settings,
+        agreement: splitAgreement,
+        paymentTokenMint: NATIVE_MINT,
+        projectTokenMint: NATIVE_MINT,
+        paymentTokenAccount: seeds("payment"),
+        projectTokenAccount: seeds("project"),
+        companyWallet: companyWallet.publicKey,
+        tokenSeller: tokenSeller.publicKey,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      }).rpc();
+
+    try {
+      await initializeAgreement([
+        { recipient: tokenSeller.publicKey, basisPoints: 7000 },
+        { recipient: advisor.publicKey, basisPoints: 2000 },
+      ]);
+      assert.fail("split not adding up to 100% accepted");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidRevenueSplit");
+    }
+    await initializeAgreement([
+      { recipient: tokenSeller.publicKey, basisPoints: 7000 },
+      { recipient: advisor.publicKey, basisPoints: 3000 },
+    ]);
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(600), tokenAmount: new anchor.BN(100), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement: splitAgreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: splitAgreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    await program.methods.depositStablecoins({ amount: new anchor.BN(600), memo: null, receiptNonce: null }).accounts({
+      agreement: splitAgreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet: blocklist(investor.publicKey),
+      destinationTokenAccount: seeds("payment"),
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    const tokenAccount = async (wallet: anchor.web3.PublicKey) => (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      wallet
+    )).address;
+    const sellerTokenAccount = await tokenAccount(tokenSeller.publicKey);
+    const advisorTokenAccount = await tokenAccount(advisor.publicKey);
+    const sellerBefore = await program.provider.connection.getTokenAccountBalance(sellerTokenAccount);
+
+    await program.methods.withdrawFunds({}).accounts({
+      settings,
+      agreement: splitAgreement,
+      paymentMint: null,
+      paymentTokenAccount: seeds("payment"),
+      blockedWallet: blocklist(tokenSeller.publicKey),
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      tokenSeller: null,
+      sellerTokenAccount: null,
+      ownerTokenAccount: await tokenAccount(program.provider.wallet.publicKey),
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).remainingAccounts([
+      { pubkey: sellerTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(tokenSeller.publicKey), isWritable: false, isSigner: false },
+      { pubkey: advisorTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(advisor.publicKey), isWritable: false, isSigner: false },
+    ]).signers([tokenSeller]).rpc();
+
+    // 600 less 2.5% commission leaves 585, the seller's 70% rounds down and the advisor takes the rest
+    const sellerAfter = await program.provider.connection.getTokenAccountBalance(sellerTokenAccount);
+    const advisorBalance = await program.provider.connection.getTokenAccountBalance(advisorTokenAccount);
+    assert.equal(Number(sellerAfter.value.amount) - Number(sellerBefore.value.amount), 409);
+    assert.equal(advisorBalance.value.amount, "176");
+
+    const agreementAccount = await program.account.agreement.fetch(splitAgreement);
+    assert.deepEqual(agreementAccount.revenueSplit.map((share) => share.paid.toNumber()), [409, 176]);
+    assert.isTrue(agreementAccount.fundsCommisionCollected);
+  });
+
+  it("Escrows the share of a blocked recipient until it is released", async () => {
+    const agreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const agreement = seeds("agreement");
+    const advisor = anchor.web3.Keypair.generate();
+    const investor = anchor.web3.Keypair.generate();
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), agreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    await airdrop(program, investor.publicKey);
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      revenueSplit: [
+        { recipient: tokenSeller.publicKey, basisPoints: 7000 },
+        { recipient: advisor.publicKey, basisPoints: 3000 },
+      ],
+    })).accounts({
+      settings,
+      agreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(600), tokenAmount: new anchor.BN(100), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    await program.methods.depositStablecoins({ amount: new anchor.BN(600), memo: null, receiptNonce: null }).accounts({
+      agreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet: blocklist(investor.publicKey),
+      destinationTokenAccount: seeds("payment"),
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    await program.methods.blockWallet({ wallet: advisor.publicKey }).accounts({
+      settings,
+      blockedWallet: blocklist(advisor.publicKey),
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    const tokenAccount = async (wallet: anchor.web3.PublicKey) => (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      wallet
+    )).address;
+    const sellerTokenAccount = await tokenAccount(tokenSeller.publicKey);
+    const advisorTokenAccount = await tokenAccount(advisor.publicKey);
+    const sellerBefore = await program.provider.connection.getTokenAccountBalance(sellerTokenAccount);
+
+    // The blocked advisor does not hold up the seller's share
+    await program.methods.withdrawFunds({}).accounts({
+      settings,
+      agreement,
+      paymentMint: null,
+      paymentTokenAccount: seeds("payment"),
+      blockedWallet: blocklist(tokenSeller.publicKey),
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      tokenSeller: null,
+      sellerTokenAccount: null,
+      ownerTokenAccount: await tokenAccount(program.provider.wallet.publicKey),
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).remainingAccounts([
+      { pubkey: sellerTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(tokenSeller.publicKey), isWritable: false, isSigner: false },
+      { pubkey: advisorTokenAccount, isWritable: true, isSigner: false },
+      { pubkey: blocklist(advisor.publicKey), isWritable: false, isSigner: false },
+    ]).signers([tokenSeller]).rpc();
+
+    const sellerAfter = await program.provider.connection.getTokenAccountBalance(sellerTokenAccount);
+    assert.equal(Number(sellerAfter.value.amount) - Number(sellerBefore.value.amount), 409);
+    assert.equal((await program.provider.connection.getTokenAccountBalance(advisorTokenAccount)).value.amount, "0");
+    let agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.deepEqual(agreementAccount.revenueSplit.map((share) => share.escrowed.toNumber()), [0, 176]);
+
+    const release = () => program.methods.releaseRevenueShare({ index: 1 }).accounts({
+      settings,
+      agreement,
+      paymentMint: null,
+      paymentTokenAccount: seeds("payment"),
+      blockedWallet: blocklist(advisor.publicKey),
+      recipientTokenAccount: advisorTokenAccount,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).rpc();
+    try {
+      await release();
+      assert.fail("share released to a blocked recipient");
+    } catch (err) {
+      assert.include(err.toString(), "WalletBlocked");
+    }
+
+    await program.methods.unblockWallet({ wallet: advisor.publicKey }).accounts({
+      settings,
+      blockedWallet: blocklist(advisor.publicKey),
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+    }).rpc();
+    await release();
+
+    assert.equal((await program.provider.connection.getTokenAccountBalance(advisorTokenAccount)).value.amount, "176");
+    agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.deepEqual(agreementAccount.revenueSplit.map((share) => share.paid.toNumber()), [409, 176]);
+    assert.deepEqual(agreementAccount.revenueSplit.map((share) => share.escrowed.toNumber()), [0, 0]);
+  });
+});
@@ -0,0 +1,128 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("roles", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Lets an operator create agreements until the role is revoked", async () => {
+    const operator = anchor.web3.Keypair.generate();
+    const signature = await program.provider.connection.requestAirdrop(operator.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    const operatorRole = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("role"), settings.toBuffer(), Uint8Array.from([0]), operator.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+    await program.methods.grantRole({ wallet: operator.publicKey, kind: { operator: {} } }).accounts({
+      settings,
+      role: operatorRole,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    const seeds = (prefix: string, id: anchor.BN) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(id.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const initializeAgreement = (id: anchor.BN) => program.methods.initializeAgreement(defaultAgreementParams()).accounts({
+      settings,
+      agreement: seeds("agreement", id),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment", id),
+      projectTokenAccount: seeds("project", id),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      operator: operatorRole,
+      payer: operator.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).signers([operator]).rpc();
+
+    const operatorAgreementId = await nextAgreementId(program, settings);
+    await initializeAgreement(operatorAgreementId);
+    const agreementAccount = await program.account.agreement.fetch(seeds("agreement", operatorAgreementId));
+    assert.equal(agreementAccount.owner.toBase58(), operator.publicKey.toBase58());
+
+    await program.methods.revokeRole({ wallet: operator.publicKey, kind: { operator: {} } }).accounts({
+      settings,
+      role: operatorRole,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+    }).rpc();
+
+    try {
+      await initializeAgreement(await nextAgreementId(program, settings));
+      assert.fail("revoked operator created an agreement");
+    } catch (err) {
+      assert.include(err.toString(), "AccountNotInitialized");
+    }
+  });
+
+  it("Issues and revokes KYC attestations by a compliance officer", async () => {
+    const officer = anchor.web3.Keypair.generate();
+    const signature = await program.provider.connection.requestAirdrop(officer.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+
+    const compliance = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("role"), settings.toBuffer(), Uint8Array.from([1]), officer.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    await program.methods.grantRole({ wallet: officer.publicKey, kind: { compliance: {} } }).accounts({
+      settings,
+      role: compliance,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    const wallet = anchor.web3.Keypair.generate().publicKey;
+    const attestation = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("attestation"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+
+    await program.methods.issueAttestation({ wallet, jurisdiction: 840, expiresAt }).accounts({
+      settings,
+      compliance,
+      attestation,
+      payer: officer.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).signers([officer]).rpc();
+
+    const attestationAccount = await program.account.attestation.fetch(attestation);
+    assert.equal(attestationAccount.wallet.toBase58(), wallet.toBase58());
+    assert.equal(attestationAccount.jurisdiction, 840);
+    assert.equal(attestationAccount.expiresAt.toString(), expiresAt.toString());
+
+    await program.methods.revokeAttestation({ wallet }).accounts({
+      settings,
+      compliance,
+      attestation,
+      payer: officer.publicKey,
+    }).signers([officer]).rpc();
+
+    assert.isNull(await program.account.attestation.fetchNullable(attestation));
+  });
+});
@@ -0,0 +1,388 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("seller-bond", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+  let sellerTokenAccount: anchor.web3.PublicKey;
+  let companyTokenAccount: anchor.web3.PublicKey;
+
+  const balance = async (tokenAccount: anchor.web3.PublicKey) =>
+    Number((await program.provider.connection.getTokenAccountBalance(tokenAccount)).value.amount);
+
+  // Creates an agreement the seller accepts, posting a bond of 50
+  const bonded = async (deliveryDeadline: number, toInvestors = false, claimWindow = 0, overrides = {}) => {
+    const bondedAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(bondedAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      sellerBond: {
+        amount: new anchor.BN(50),
+        deliveryDeadline: new anchor.BN(deliveryDeadline),
+        toInvestors,
+        claimWindow: new anchor.BN(claimWindow),
+      },
+      ...overrides,
+    })).accounts({
+      settings,
+      agreement: seeds("agreement"),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: seeds("agreement"),
+      paymentTokenMint: NATIVE_MINT,
+      bondTokenAccount: seeds("bond"),
+      sellerPaymentTokenAccount: sellerTokenAccount,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    assert.equal(await balance(seeds("bond")), 50);
+    return seeds;
+  };
+  const deliver = (seeds: (prefix: string) => anchor.web3.PublicKey) => program.methods.depositProjectTokens({}).accounts({
+    agreement: seeds("agreement"),
+    projectTokenAccount: seeds("project"),
+    sellerTokenAccount,
+    companyTokenAccount,
+    bondTokenAccount: seeds("bond"),
+    sellerPaymentTokenAccount: sellerTokenAccount,
+    payer: tokenSeller.publicKey,
+    systemProgram: anchor.web3.SystemProgram.programId,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+  }).signers([tokenSeller]).rpc();
+  const slash = (seeds: (prefix: string) => anchor.web3.PublicKey) => program.methods.slashSellerBond({}).accounts({
+    settings,
+    agreement: seeds("agreement"),
+    bondTokenAccount: seeds("bond"),
+    companyTokenAccount,
+    operator: null,
+    payer: program.provider.wallet.publicKey,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+  }).rpc();
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+
+    sellerTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      tokenSeller.publicKey
+    )).address;
+    companyTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      companyWallet.publicKey
+    )).address;
+    // Bond of 50 plus 100 project tokens for each agreement
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: sellerTokenAccount,
+        lamports: 600,
+      }),
+      createSyncNativeInstruction(sellerTokenAccount)
+    ));
+  });
+
+  it("Returns the seller bond on time and slashes it when delivery is late", async () => {
+    const onTime = await bonded(Math.floor(Date.now() / 1000) + 3600);
+    await deliver(onTime);
+    assert.isTrue((await program.account.agreement.fetch(onTime("agreement"))).bondReturned);
+    assert.equal(await balance(onTime("bond")), 0);
+
+    const deliveryDeadline = Math.floor(Date.now() / 1000) + 5;
+    const late = await bonded(deliveryDeadline);
+    try {
+      await slash(late);
+      assert.fail("bond slashed before the delivery deadline");
+    } catch (err) {
+      assert.include(err.toString(), "SellerBondNotSlashable");
+    }
+
+    await new Promise(resolve => setTimeout(resolve, (deliveryDeadline + 2) * 1000 - Date.now()));
+    // Only the owner or an operator slashes, as the slash fixes the investors sharing the bond
+    try {
+      await program.methods.slashSellerBond({}).accounts({
+        settings,
+        agreement: late("agreement"),
+        bondTokenAccount: late("bond"),
+        companyTokenAccount,
+        operator: null,
+        payer: tokenSeller.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+      }).signers([tokenSeller]).rpc();
+      assert.fail("bond slashed by the seller");
+    } catch (err) {
+      assert.include(err.toString(), "NotAuthorized");
+    }
+
+    const companyBefore = await balance(companyTokenAccount);
+    await slash(late);
+    assert.equal(await balance(companyTokenAccount) - companyBefore, 50);
+
+    // Late delivery still goes through but the bond stays forfeited
+    await deliver(late);
+    const lateAgreement = await program.account.agreement.fetch(late("agreement"));
+    assert.isTrue(lateAgreement.bondSlashed);
+    assert.isFalse(lateAgreement.bondReturned);
+  });
+
+  it("Returns the bond of an agreement cancelled before the delivery deadline", async () => {
+    const deliveryDeadline = Math.floor(Date.now() / 1000) + 5;
+    const cancelled = await bonded(deliveryDeadline);
+
+    // Tokens sent to the vault without being processed let the owner cancel right away
+    await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: program.provider.wallet.publicKey,
+        toPubkey: cancelled("project"),
+        lamports: 10,
+      }),
+      createSyncNativeInstruction(cancelled("project"))
+    ));
+    await program.methods.cancelAgreement({}).accounts({
+      settings,
+      agreement: cancelled("agreement"),
+      projectTokenAccount: cancelled("project"),
+      investor: null,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).rpc();
+
+    await new Promise(resolve => setTimeout(resolve, (deliveryDeadline + 2) * 1000 - Date.now()));
+    try {
+      await slash(cancelled);
+      assert.fail("bond of a cancelled agreement slashed");
+    } catch (err) {
+      assert.include(err.toString(), "SellerBondNotSlashable");
+    }
+
+    const sellerBefore = await balance(sellerTokenAccount);
+    await program.methods.reclaimSellerBond({}).accounts({
+      agreement: cancelled("agreement"),
+      bondTokenAccount: cancelled("bond"),
+      sellerPaymentTokenAccount: sellerTokenAccount,
+      payer: tokenSeller.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).signers([tokenSeller]).rpc();
+    assert.equal(await balance(sellerTokenAccount) - sellerBefore, 50);
+    assert.isTrue((await program.account.agreement.fetch(cancelled("agreement"))).bondReturned);
+  });
+
+  it("Sweeps bond shares left unclaimed after the claim window to the company", async () => {
+    const deliveryDeadline = Math.floor(Date.now() / 1000) + 5;
+    const late = await bonded(deliveryDeadline, true, 3);
+    const investor = anchor.web3.Keypair.generate();
+    await airdrop(program, investor.publicKey);
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), late("agreement").toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blockedWallet = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+    await new Promise(resolve => setTimeout(resolve, (deliveryDeadline + 2) * 1000 - Date.now()));
+    await deliver(late);
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(600), tokenAmount: new anchor.BN(100), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement: late("agreement"),
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+    await program.methods.depositStablecoins({ amount: new anchor.BN(600), memo: null, receiptNonce: null }).accounts({
+      agreement: late("agreement"),
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet,
+      destinationTokenAccount: late("payment"),
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+    await slash(late);
+
+    const sweep = () => program.methods.sweepBondShares({}).accounts({
+      agreement: late("agreement"),
+      bondTokenAccount: late("bond"),
+      companyTokenAccount,
+      payer: program.provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).rpc();
+    try {
+      await sweep();
+      assert.fail("bond shares swept during the claim window");
+    } catch (err) {
+      assert.include(err.toString(), "BondClaimWindowOpen");
+    }
+
+    await new Promise(resolve => setTimeout(resolve, 5000));
+    try {
+      await program.methods.claimBondShare({}).accounts({
+        agreement: late("agreement"),
+        investor: investorAccount,
+        blockedWallet,
+        bondTokenAccount: late("bond"),
+        investorTokenAccount: (await getOrCreateAssociatedTokenAccount(
+          program.provider.connection,
+          program.provider.wallet.payer,
+          NATIVE_MINT,
+          investor.publicKey
+        )).address,
+        payer: investor.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      }).signers([investor]).rpc();
+      assert.fail("bond share claimed after the claim window");
+    } catch (err) {
+      assert.include(err.toString(), "BondClaimWindowClosed");
+    }
+
+    const companyBefore = await balance(companyTokenAccount);
+    await sweep();
+    assert.equal(await balance(companyTokenAccount) - companyBefore, 50);
+    assert.equal(await balance(late("bond")), 0);
+  });
+
+  it("Pays the bond share of an investor refunded after the slash", async () => {
+    const deliveryDeadline = Math.floor(Date.now() / 1000) + 5;
+    const fundingDeadline = deliveryDeadline + 10;
+    const late = await bonded(deliveryDeadline, true, 3600, {
+      softCap: new anchor.BN(600),
+      fundingDeadline: new anchor.BN(fundingDeadline),
+    });
+    const investor = anchor.web3.Keypair.generate();
+    await airdrop(program, investor.publicKey);
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), late("agreement").toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blockedWallet = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const investorTokenAccount = (await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      program.provider.wallet.payer,
+      NATIVE_MINT,
+      investor.publicKey
+    )).address;
+
+    await new Promise(resolve => setTimeout(resolve, (deliveryDeadline + 2) * 1000 - Date.now()));
+    await deliver(late);
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(300), tokenAmount: new anchor.BN(50), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement: late("agreement"),
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+    await program.methods.depositStablecoins({ amount: new anchor.BN(300), memo: null, receiptNonce: null }).accounts({
+      agreement: late("agreement"),
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet,
+      destinationTokenAccount: late("payment"),
+      payerTokenAccount: null,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+    await slash(late);
+
+    // Soft cap missed by the deadline, the investor takes the deposit back
+    await new Promise(resolve => setTimeout(resolve, (fundingDeadline + 2) * 1000 - Date.now()));
+    await program.methods.cancelAgreement({}).accounts({
+      settings,
+      agreement: late("agreement"),
+      projectTokenAccount: late("project"),
+      investor: null,
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).rpc();
+    await program.methods.withdrawCancelledFunds({}).accounts({
+      agreement: late("agreement"),
+      investor: investorAccount,
+      agreementTokenAccount: late("payment"),
+      destinationTokenAccount: investorTokenAccount,
+      unwrapTokenAccount: null,
+      nativeMint: null,
+      destination: null,
+      blockedWallet,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+    assert.isFalse((await program.account.investor.fetch(investorAccount)).investorDeposited);
+
+    const investorBefore = await balance(investorTokenAccount);
+    await program.methods.claimBondShare({}).accounts({
+      agreement: late("agreement"),
+      investor: investorAccount,
+      blockedWallet,
+      bondTokenAccount: late("bond"),
+      investorTokenAccount,
+      payer: investor.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).signers([investor]).rpc();
+    assert.equal(await balance(investorTokenAccount) - investorBefore, 50);
+    assert.isTrue((await program.account.investor.fetch(investorAccount)).bondClaimed);
+  });
+});
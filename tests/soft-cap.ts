@@ -0,0 +1,163 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createSyncNativeInstruction, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("soft-cap", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Finalizes a soft cap raise and returns unsold tokens", async () => {
+    const softCapAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(softCapAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const softCapAgreement = seeds("agreement");
+    const investor = anchor.web3.Keypair.generate();
+    const investorAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), softCapAgreement.toBuffer(), investor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const signature = await program.provider.connection.requestAirdrop(investor.publicKey, 1000000000);
+    await program.provider.connection.confirmTransaction(signature);
+    const fundingDeadline = Math.floor(Date.now() / 1000) + 5;
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      softCap: new anchor.BN(300),
+      fundingDeadline: new anchor.BN(fundingDeadline),
+    })).accounts({
+      settings,
+      agreement: softCapAgreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(300), tokenAmount: new anchor.BN(50), wallet: investor.publicKey, referral: null }],
+    }).accounts({
+      settings,
+      agreement: softCapAgreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorAccount, isWritable: true, isSigner: false }]).rpc();
+
+    await program.methods.acceptAgreement({
+      expectedPayment: new anchor.BN(600),
+      expectedTokens: new anchor.BN(100),
+      holdDuration: new anchor.BN(1),
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      documentHash: Array(32).fill(0),
+    }).accounts({
+      agreement: softCapAgreement,
+      paymentTokenMint: null,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+    }).signers([tokenSeller]).rpc();
+
+    const wrap = async (owner: anchor.web3.PublicKey, lamports: number) => {
+      const tokenAccount = (await getOrCreateAssociatedTokenAccount(
+        program.provider.connection,
+        program.provider.wallet.payer,
+        NATIVE_MINT,
+        owner
+      )).address;
+      await program.provider.sendAndConfirm(new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: program.provider.wallet.publicKey,
+          toPubkey: tokenAccount,
+          lamports,
+        }),
+        createSyncNativeInstruction(tokenAccount)
+      ));
+      return tokenAccount;
+    };
+    const sellerTokenAccount = await wrap(tokenSeller.publicKey, 100);
+    const investorTokenAccount = await wrap(investor.publicKey, 300);
+    const companyTokenAccount = await wrap(companyWallet.publicKey, 0);
+
+    await program.methods.depositProjectTokens({}).accounts({
+      agreement: softCapAgreement,
+      projectTokenAccount: seeds("project"),
+      sellerTokenAccount,
+      companyTokenAccount,
+      bondTokenAccount: null,
+      sellerPaymentTokenAccount: null,
+      payer: tokenSeller.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([tokenSeller]).rpc();
+
+    await program.methods.depositStablecoins({ amount: new anchor.BN(300), memo: null, receiptNonce: null }).accounts({
+      agreement: softCapAgreement,
+      investor: investorAccount,
+      acknowledgement: null,
+      attestation: null,
+      paymentReceipt: null,
+      paymentMint: null,
+      priceFeed: null,
+      blockedWallet: blocklist(investor.publicKey),
+      destinationTokenAccount: seeds("payment"),
+      payerTokenAccount: investorTokenAccount,
+      payer: investor.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).signers([investor]).rpc();
+
+    await new Promise(resolve => setTimeout(resolve, (fundingDeadline + 2) * 1000 - Date.now()));
+
+    await program.methods.finalizeFunding({}).accounts({
+      agreement: softCapAgreement,
+      payer: program.provider.wallet.publicKey,
+      clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+    }).rpc();
+
+    const agreementAccount = await program.account.agreement.fetch(softCapAgreement);
+    assert.ok(agreementAccount.holdDurationStart.toNumber() > 0);
+    assert.equal(agreementAccount.tokensSold.toNumber(), 50);
+
+    const tx = await program.methods.withdrawUnsoldTokens({}).accounts({
+      agreement: softCapAgreement,
+      projectTokenAccount: seeds("project"),
+      blockedWallet: blocklist(tokenSeller.publicKey),
+      sellerTokenAccount,
+      payer: tokenSeller.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    }).signers([tokenSeller]).rpc();
+    console.log("Your transaction signature", tx);
+
+    const sellerBalance = await program.provider.connection.getTokenAccountBalance(sellerTokenAccount);
+    assert.equal(sellerBalance.value.amount, "50");
+  });
+});
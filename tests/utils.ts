@@ -0,0 +1,81 @@
+import * as anchor from "@coral-xyz/anchor";
+import { IdlTypes, Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+
+export type AgreementParams = IdlTypes<TokenTimeLockAgreement>["initializeAgreementParams"];
+
+export type Tenant = {
+  tenantId: anchor.BN;
+  tenantSeed: Uint8Array;
+  settings: anchor.web3.PublicKey;
+};
+
+export function programDataAddress(program: Program<TokenTimeLockAgreement>): anchor.web3.PublicKey {
+  return anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  )[0];
+}
+
+export function randomTenant(program: Program<TokenTimeLockAgreement>): Tenant {
+  const tenantId = new anchor.BN(Math.floor(Math.random() * 1_000_000) + 1);
+  const tenantSeed = Uint8Array.from(tenantId.toBuffer('le', 8));
+  const settings = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("settings"), tenantSeed],
+    program.programId
+  )[0];
+  return { tenantId, tenantSeed, settings };
+}
+
+// Creates a tenant owned by the provider wallet, charging 2.5% and accepting any payment mint
+export async function initializeTenant(program: Program<TokenTimeLockAgreement>): Promise<Tenant> {
+  const tenant = randomTenant(program);
+  await program.methods.initialize({
+    commissionBasisPoints: 250,
+    cancelTimeout: new anchor.BN(60),
+    owner: program.provider.wallet.publicKey,
+    tenantId: tenant.tenantId,
+    allowedMints: [],
+  }).accounts({
+    settings: tenant.settings,
+    program: program.programId,
+    programData: programDataAddress(program),
+    authority: program.provider.wallet.publicKey,
+    payer: program.provider.wallet.publicKey,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  }).rpc();
+  return tenant;
+}
+
+export async function airdrop(program: Program<TokenTimeLockAgreement>, wallet: anchor.web3.PublicKey) {
+  const signature = await program.provider.connection.requestAirdrop(wallet, 1000000000);
+  await program.provider.connection.confirmTransaction(signature);
+}
+
+// Id the tenant's counter assigns to the next agreement created without an explicit one
+export async function nextAgreementId(program: Program<TokenTimeLockAgreement>, settings: anchor.web3.PublicKey): Promise<anchor.BN> {
+  return (await program.account.settings.fetch(settings)).nextAgreementId;
+}
+
+// Parameters of a 600 for 100 token agreement with every optional feature off, `overrides` replaces fields
+export function defaultAgreementParams(overrides: Partial<AgreementParams> = {}): AgreementParams {
+  return {
+    agreementId: null,
+    expectedPayment: new anchor.BN(600),
+    expectedTokens: new anchor.BN(100),
+    holdDuration: new anchor.BN(1),
+    documentHash: Array(32).fill(0),
+    documentUri: "",
+    companyAcceptanceRequired: false,
+    complianceRequired: false,
+    depositWindow: new anchor.BN(0),
+    softCap: new anchor.BN(0),
+    fundingDeadline: new anchor.BN(0),
+    publicSale: null,
+    commitDeadline: new anchor.BN(0),
+    sellerBond: null,
+    revenueSplit: null,
+    feeSplit: null,
+    ...overrides,
+  };
+}
@@ -0,0 +1,248 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenTimeLockAgreement } from "../target/types/token_time_lock_agreement";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT } from '@solana/spl-token';
+import { assert } from "chai";
+import { airdrop, initializeTenant, nextAgreementId, defaultAgreementParams } from "./utils";
+
+describe("waitlist", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.tokenTimeLockAgreement as Program<TokenTimeLockAgreement>;
+
+  const companyWallet = anchor.web3.Keypair.generate();
+  const tokenSeller = anchor.web3.Keypair.generate();
+
+  let tenantSeed: Uint8Array;
+  let settings: anchor.web3.PublicKey;
+
+  before(async () => {
+    ({ tenantSeed, settings } = await initializeTenant(program));
+    await airdrop(program, tokenSeller.publicKey);
+  });
+
+  it("Backfills an expired allocation from the waitlist", async () => {
+    const waitlistAgreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(waitlistAgreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const waitlistAgreement = seeds("agreement");
+    const investorPda = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), waitlistAgreement.toBuffer(), wallet.toBuffer()],
+      program.programId
+    )[0];
+    const late = anchor.web3.Keypair.generate().publicKey;
+    const waitlisted = anchor.web3.Keypair.generate().publicKey;
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      depositWindow: new anchor.BN(1),
+    })).accounts({
+      settings,
+      agreement: waitlistAgreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    await program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(600), tokenAmount: new anchor.BN(100), wallet: late, referral: null }],
+    }).accounts({
+      settings,
+      agreement: waitlistAgreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorPda(late), isWritable: true, isSigner: false }]).rpc();
+
+    const waitlistEntry = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("waitlist"), waitlistAgreement.toBuffer(), waitlisted.toBuffer()],
+      program.programId
+    )[0];
+    await program.methods.addToWaitlist({ wallet: waitlisted }).accounts({
+      settings,
+      agreement: waitlistAgreement,
+      waitlistEntry,
+      investor: investorPda(waitlisted),
+      blockedWallet: anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("blocklist"), tenantSeed, waitlisted.toBuffer()],
+        program.programId
+      )[0],
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    const tx = await program.methods.expireAllocation({}).accounts({
+      settings,
+      agreement: waitlistAgreement,
+      investor: investorPda(late),
+      referrer: null,
+      waitlistEntry,
+      nextInvestor: investorPda(waitlisted),
+      agreementOwner: program.provider.wallet.publicKey,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+    console.log("Your transaction signature", tx);
+
+    assert.isNull(await program.account.investor.fetchNullable(investorPda(late)));
+    const investorAccount = await program.account.investor.fetch(investorPda(waitlisted));
+    assert.equal(investorAccount.requiredAmount.toNumber(), 600);
+    assert.equal(investorAccount.tokenAllocation.toNumber(), 100);
+    const agreementAccount = await program.account.agreement.fetch(waitlistAgreement);
+    assert.equal(agreementAccount.waitlistHead.toNumber(), 1);
+  });
+
+  it("Keeps the waitlist unique and skips a head that already holds an allocation", async () => {
+    const agreementId = await nextAgreementId(program, settings);
+    const seeds = (prefix: string) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), tenantSeed, Uint8Array.from(agreementId.toBuffer('le', 8))],
+      program.programId
+    )[0];
+    const agreement = seeds("agreement");
+    const investorPda = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("investor"), agreement.toBuffer(), wallet.toBuffer()],
+      program.programId
+    )[0];
+    const waitlistEntry = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("waitlist"), agreement.toBuffer(), wallet.toBuffer()],
+      program.programId
+    )[0];
+    const blocklist = (wallet: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist"), tenantSeed, wallet.toBuffer()],
+      program.programId
+    )[0];
+    const [late, head, next, blocked] = [0, 1, 2, 3].map(() => anchor.web3.Keypair.generate().publicKey);
+    const stranger = anchor.web3.Keypair.generate();
+    await airdrop(program, stranger.publicKey);
+
+    await program.methods.initializeAgreement(defaultAgreementParams({
+      depositWindow: new anchor.BN(1),
+    })).accounts({
+      settings,
+      agreement,
+      paymentTokenMint: NATIVE_MINT,
+      projectTokenMint: NATIVE_MINT,
+      paymentTokenAccount: seeds("payment"),
+      projectTokenAccount: seeds("project"),
+      companyWallet: companyWallet.publicKey,
+      tokenSeller: tokenSeller.publicKey,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    }).rpc();
+
+    const addInvestor = (wallet: anchor.web3.PublicKey) => program.methods.addInvestors({
+      allocations: [{ amount: new anchor.BN(300), tokenAmount: new anchor.BN(50), wallet, referral: null }],
+    }).accounts({
+      settings,
+      agreement,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).remainingAccounts([{ pubkey: investorPda(wallet), isWritable: true, isSigner: false }]).rpc();
+    const addToWaitlist = (wallet: anchor.web3.PublicKey) => program.methods.addToWaitlist({ wallet }).accounts({
+      settings,
+      agreement,
+      waitlistEntry: waitlistEntry(wallet),
+      investor: investorPda(wallet),
+      blockedWallet: blocklist(wallet),
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    await addInvestor(late);
+    await addToWaitlist(head);
+    await addToWaitlist(next);
+    try {
+      await addToWaitlist(head);
+      assert.fail("wallet queued twice");
+    } catch (err) {
+      // The entry address is taken, which the system program reports as error 0
+      assert.include(err.toString(), "custom program error: 0x0");
+    }
+    try {
+      await addToWaitlist(late);
+      assert.fail("investor queued");
+    } catch (err) {
+      assert.include(err.toString(), "InvestorAlreadyExists");
+    }
+    await program.methods.blockWallet({ wallet: blocked }).accounts({
+      settings,
+      blockedWallet: blocklist(blocked),
+      proposal: null,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+    try {
+      await addToWaitlist(blocked);
+      assert.fail("blocked wallet queued");
+    } catch (err) {
+      assert.include(err.toString(), "WalletBlocked");
+    }
+
+    // The head of the queue gets an allocation of its own before one expires
+    await addInvestor(head);
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    const expire = (entry: anchor.web3.PublicKey, payer: anchor.web3.Keypair | null) => program.methods.expireAllocation({}).accounts({
+      settings,
+      agreement,
+      investor: investorPda(late),
+      referrer: null,
+      waitlistEntry: waitlistEntry(entry),
+      nextInvestor: investorPda(entry),
+      agreementOwner: program.provider.wallet.publicKey,
+      operator: null,
+      payer: payer ? payer.publicKey : program.provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }).signers(payer ? [payer] : []).rpc();
+    try {
+      await expire(head, stranger);
+      assert.fail("allocation expired by a stranger");
+    } catch (err) {
+      assert.include(err.toString(), "NotAuthorized");
+    }
+    try {
+      await expire(head, null);
+      assert.fail("head with an allocation served");
+    } catch (err) {
+      assert.include(err.toString(), "InvestorAlreadyExists");
+    }
+    try {
+      await expire(next, null);
+      assert.fail("waitlist served out of order");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidWaitlistEntry");
+    }
+
+    await program.methods.skipWaitlistEntry({}).accounts({
+      settings,
+      agreement,
+      waitlistEntry: waitlistEntry(head),
+      agreementOwner: program.provider.wallet.publicKey,
+      operator: null,
+      payer: program.provider.wallet.publicKey,
+    }).rpc();
+    assert.isNull(await program.account.waitlistEntry.fetchNullable(waitlistEntry(head)));
+
+    await expire(next, null);
+    assert.isNull(await program.account.investor.fetchNullable(investorPda(late)));
+    assert.equal((await program.account.investor.fetch(investorPda(next))).requiredAmount.toNumber(), 300);
+    const agreementAccount = await program.account.agreement.fetch(agreement);
+    assert.equal(agreementAccount.waitlistHead.toNumber(), 2);
+  });
+});